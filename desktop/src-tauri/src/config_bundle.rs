@@ -0,0 +1,186 @@
+//! Archive format used by `export_config` and `import_config`
+//!
+//! The archive is a tar file holding `manifest.json`, the desktop settings
+//! at `desktop/settings.json`, and the backend's config files under
+//! `backend/`. Anything else in an archive is rejected on import.
+
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+
+/// Current archive format, bumped on incompatible layout changes
+pub const FORMAT_VERSION: u32 = 1;
+
+const MANIFEST: &str = "manifest.json";
+const DESKTOP_SETTINGS: &str = "desktop/settings.json";
+const BACKEND_PREFIX: &str = "backend/";
+
+// Guard against absurd entries; real config files are a few KB
+const MAX_ENTRY_BYTES: u64 = 16 * 1024 * 1024;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Manifest {
+    pub format: u32,
+    pub app_version: String,
+    /// Seconds since the Unix epoch
+    pub created_at: u64,
+}
+
+/// Contents of a config archive
+pub struct ConfigBundle {
+    pub manifest: Manifest,
+    pub desktop_settings: Vec<u8>,
+    /// Backend config files by file name (e.g. "profiles.yaml")
+    pub backend_files: Vec<(String, Vec<u8>)>,
+}
+
+fn append(builder: &mut tar::Builder<Vec<u8>>, path: &str, data: &[u8], mtime: u64) -> Result<(), String> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(mtime);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, path, data)
+        .map_err(|e| format!("Failed to add {} to archive: {}", path, e))
+}
+
+impl ConfigBundle {
+    /// Serialize the bundle as a tar archive
+    pub fn pack(&self) -> Result<Vec<u8>, String> {
+        let mut builder = tar::Builder::new(Vec::new());
+        let manifest = serde_json::to_vec_pretty(&self.manifest).map_err(|e| e.to_string())?;
+        let mtime = self.manifest.created_at;
+        append(&mut builder, MANIFEST, &manifest, mtime)?;
+        append(&mut builder, DESKTOP_SETTINGS, &self.desktop_settings, mtime)?;
+        for (name, data) in &self.backend_files {
+            append(&mut builder, &format!("{}{}", BACKEND_PREFIX, name), data, mtime)?;
+        }
+        builder.into_inner().map_err(|e| format!("Failed to finish archive: {}", e))
+    }
+
+    /// Parse and validate an archive made by `pack`
+    ///
+    /// Only backend files named in `backend_names` are accepted, so an
+    /// archive can't write anywhere else in the data dir.
+    pub fn unpack(bytes: &[u8], backend_names: &[&str]) -> Result<Self, String> {
+        let mut manifest = None;
+        let mut desktop_settings = None;
+        let mut backend_files = Vec::new();
+
+        let mut archive = tar::Archive::new(bytes);
+        let entries = archive.entries().map_err(|e| format!("Not a config archive: {}", e))?;
+        for entry in entries {
+            let mut entry = entry.map_err(|e| format!("Corrupt config archive: {}", e))?;
+            let path = entry.path().map_err(|e| e.to_string())?.to_string_lossy().to_string();
+            if entry.size() > MAX_ENTRY_BYTES {
+                return Err(format!("{} is too large", path));
+            }
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+
+            match path.as_str() {
+                MANIFEST => {
+                    manifest = Some(
+                        serde_json::from_slice::<Manifest>(&data).map_err(|e| format!("Invalid manifest: {}", e))?,
+                    );
+                }
+                DESKTOP_SETTINGS => desktop_settings = Some(data),
+                _ => match path.strip_prefix(BACKEND_PREFIX) {
+                    Some(name) if backend_names.contains(&name) => {
+                        backend_files.push((name.to_string(), data));
+                    }
+                    _ => return Err(format!("Unexpected file in config archive: {}", path)),
+                },
+            }
+        }
+
+        let manifest = manifest.ok_or("Config archive has no manifest")?;
+        if manifest.format != FORMAT_VERSION {
+            return Err(format!(
+                "Config archive format {} is not supported (expected {})",
+                manifest.format, FORMAT_VERSION
+            ));
+        }
+        Ok(Self {
+            manifest,
+            desktop_settings: desktop_settings.ok_or("Config archive has no desktop settings")?,
+            backend_files,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BACKEND_NAMES: &[&str] = &["profiles.yaml", "settings.yaml"];
+
+    fn bundle() -> ConfigBundle {
+        ConfigBundle {
+            manifest: Manifest { format: FORMAT_VERSION, app_version: "1.0.0".to_string(), created_at: 1_700_000_000 },
+            desktop_settings: b"{}".to_vec(),
+            backend_files: vec![("profiles.yaml".to_string(), b"profiles: []".to_vec())],
+        }
+    }
+
+    fn archive(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, data) in entries {
+            append(&mut builder, path, data, 0).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    fn manifest() -> Vec<u8> {
+        serde_json::to_vec(&bundle().manifest).unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        let unpacked = ConfigBundle::unpack(&bundle().pack().unwrap(), BACKEND_NAMES).unwrap();
+        assert_eq!(unpacked.manifest.app_version, "1.0.0");
+        assert_eq!(unpacked.desktop_settings, b"{}");
+        assert_eq!(unpacked.backend_files, bundle().backend_files);
+    }
+
+    #[test]
+    fn rejects_bad_manifest() {
+        let bytes = archive(&[(MANIFEST, b"{ \"format\": 1 }"), (DESKTOP_SETTINGS, b"{}")]);
+        let err = ConfigBundle::unpack(&bytes, BACKEND_NAMES).err().unwrap();
+        assert!(err.starts_with("Invalid manifest:"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_unsupported_format() {
+        let mut bundle = bundle();
+        bundle.manifest.format = FORMAT_VERSION + 1;
+        let err = ConfigBundle::unpack(&bundle.pack().unwrap(), BACKEND_NAMES).err().unwrap();
+        assert!(err.contains("is not supported"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_missing_manifest() {
+        let bytes = archive(&[(DESKTOP_SETTINGS, b"{}")]);
+        assert_eq!(ConfigBundle::unpack(&bytes, BACKEND_NAMES).err().unwrap(), "Config archive has no manifest");
+    }
+
+    #[test]
+    fn rejects_unlisted_entries() {
+        let manifest = manifest();
+        for path in ["backend/secrets.env", "backend/nested/profiles.yaml", "desktop/other.json", "extra.txt"] {
+            let bytes = archive(&[(MANIFEST, &manifest), (DESKTOP_SETTINGS, b"{}"), (path, b"x")]);
+            let err = ConfigBundle::unpack(&bytes, BACKEND_NAMES).err().unwrap();
+            assert!(err.starts_with("Unexpected file in config archive:"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn rejects_oversized_entry() {
+        let big = vec![0; MAX_ENTRY_BYTES as usize + 1];
+        let bytes = archive(&[(MANIFEST, &manifest()), ("backend/profiles.yaml", &big)]);
+        assert_eq!(
+            ConfigBundle::unpack(&bytes, BACKEND_NAMES).err().unwrap(),
+            "backend/profiles.yaml is too large"
+        );
+    }
+}
@@ -1,14 +1,28 @@
+mod config_bundle;
+mod portable;
+mod settings;
+
 use tauri::{
-    menu::{Menu, MenuItem, PredefinedMenuItem},
-    tray::{TrayIconBuilder, TrayIconEvent},
-    Manager,
+    menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
+    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
+    Emitter, Manager,
 };
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_opener::OpenerExt;
 use tauri_plugin_updater::UpdaterExt;
 use serde::{Serialize, Deserialize};
+use std::path::PathBuf;
 use std::process::{Command, Child, Stdio};
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
 use sysinfo::{System, Pid};
 use std::time::Duration;
+use config_bundle::ConfigBundle;
+use settings::{
+    BackendEndpoints, BackendInstance, CloseBehavior, EnvInheritance, IconScope, NotificationUrgency, Settings,
+    SettingsState, TrayLeftClick, TrayToolLayout, PRIMARY_INSTANCE,
+};
 
 // Windows-specific imports for hiding console window
 #[cfg(target_os = "windows")]
@@ -18,8 +32,294 @@ use std::os::windows::process::CommandExt;
 #[cfg(target_os = "windows")]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
-// Global handle to the backend process so we can clean it up on exit
-static BACKEND_PROCESS: Mutex<Option<Child>> = Mutex::new(None);
+/// Create a `Command` that never opens a console window on Windows
+///
+/// All subprocesses (backend, port checks, ...) must be built through this so
+/// the flag can't be forgotten on a new code path.
+fn background_command<S: AsRef<std::ffi::OsStr>>(program: S) -> Command {
+    #[allow(unused_mut)]
+    let mut cmd = Command::new(program);
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    cmd
+}
+
+/// A backend process started by the app
+struct ManagedBackend {
+    /// None once a secondary instance has exited; the primary's entry is
+    /// removed instead
+    child: Option<Child>,
+    control_port: u16,
+    mcp_port: u16,
+    /// Kept up to date for secondary instances; the primary's is `backend_phase()`
+    phase: BackendPhase,
+    /// Last status of a secondary instance; the primary's is `LAST_STATUS`
+    status: Option<AppStatus>,
+}
+
+// Backends the app started, by instance id: `PRIMARY_INSTANCE` for the one
+// from the main settings, plus those from the `instances` setting. Kept so we
+// can clean them up on exit; a BTreeMap so it can be built in a const context
+// and instances list in a stable order.
+//
+// Locking discipline: never block while holding this lock. Only quick,
+// non-blocking calls (`id`, `try_wait`) happen under it; to stop a backend,
+// take the `Child` out, release the lock, then `kill` and `wait`.
+static BACKEND_INSTANCES: Mutex<std::collections::BTreeMap<String, ManagedBackend>> =
+    Mutex::new(std::collections::BTreeMap::new());
+
+/// Long-running operations that must not overlap with each other
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LifecycleState {
+    Idle,
+    Restarting,
+    InstallingUpdate,
+}
+
+// Current lifecycle operation; all transitions go through `begin_lifecycle`
+static LIFECYCLE: Mutex<LifecycleState> = Mutex::new(LifecycleState::Idle);
+
+/// Marks a lifecycle operation as in progress until dropped
+struct LifecycleGuard;
+
+impl Drop for LifecycleGuard {
+    fn drop(&mut self) {
+        if let Ok(mut state) = LIFECYCLE.lock() {
+            *state = LifecycleState::Idle;
+        }
+    }
+}
+
+/// Kinds of entries in the lifecycle log
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecycleEventKind {
+    Spawn,
+    Shutdown,
+    Crash,
+    Restart,
+    UpdateCheck,
+    UpdateInstall,
+    PortConflict,
+}
+
+/// Something the wrapper did to (or noticed about) the backend
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LifecycleEvent {
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub kind: LifecycleEventKind,
+    pub detail: String,
+}
+
+// Entries kept in the lifecycle log; older ones are dropped
+const LIFECYCLE_LOG_CAPACITY: usize = 200;
+
+// High-level timeline of what the wrapper did and why, complementing the
+// backend's own logs
+static LIFECYCLE_LOG: Mutex<std::collections::VecDeque<LifecycleEvent>> =
+    Mutex::new(std::collections::VecDeque::new());
+
+fn log_lifecycle(kind: LifecycleEventKind, detail: impl Into<String>) {
+    if let Ok(mut log) = LIFECYCLE_LOG.lock() {
+        if log.len() == LIFECYCLE_LOG_CAPACITY {
+            log.pop_front();
+        }
+        let event = LifecycleEvent { timestamp: unix_now(), kind, detail: detail.into() };
+        debug_event(EventVerbosity::Transitions, "lifecycle", || serde_json::json!(event));
+        log.push_back(event);
+    }
+}
+
+/// The most recent lifecycle events, oldest first
+#[tauri::command]
+async fn lifecycle_log(limit: Option<usize>) -> Result<Vec<LifecycleEvent>, String> {
+    let log = LIFECYCLE_LOG.lock().map_err(|_| "Lifecycle log lock poisoned".to_string())?;
+    let skip = limit.map_or(0, |limit| log.len().saturating_sub(limit));
+    Ok(log.iter().skip(skip).cloned().collect())
+}
+
+/// Start a lifecycle operation, refusing if another one is already running
+fn begin_lifecycle(next: LifecycleState) -> Result<LifecycleGuard, String> {
+    let mut state = LIFECYCLE.lock().map_err(|_| "Lifecycle state lock poisoned".to_string())?;
+    match *state {
+        LifecycleState::Idle => {
+            *state = next;
+            Ok(LifecycleGuard)
+        }
+        LifecycleState::InstallingUpdate => {
+            Err("An update is being installed; try again once it has finished".to_string())
+        }
+        LifecycleState::Restarting => {
+            Err("The gateway is restarting; try again in a moment".to_string())
+        }
+    }
+}
+
+// When the managed backend was spawned, for uptime reporting
+static BACKEND_STARTED_AT: Mutex<Option<std::time::Instant>> = Mutex::new(None);
+
+// Port of the backend control API, from the `control_port` setting
+static CONTROL_PORT: std::sync::atomic::AtomicU16 = std::sync::atomic::AtomicU16::new(6200);
+// Port of the MCP gateway, from the `mcp_port` setting
+static MCP_PORT: std::sync::atomic::AtomicU16 = std::sync::atomic::AtomicU16::new(6277);
+
+/// Base URL of the backend control API
+fn control_api() -> String {
+    format!("http://127.0.0.1:{}", CONTROL_PORT.load(Ordering::Relaxed))
+}
+
+// Client shared by the poll loop and commands, built on first use. Building it
+// can fail (e.g. the TLS backend not initialising on minimal systems), so the
+// error is kept too. `reset_poller` clears it to drop pooled connections.
+static HTTP_CLIENT: Mutex<Option<Result<reqwest::Client, String>>> = Mutex::new(None);
+
+/// The shared HTTP client, or why it couldn't be created
+fn http_client() -> Result<reqwest::Client, String> {
+    let Ok(mut client) = HTTP_CLIENT.lock() else {
+        return Err("Failed to initialise networking: client lock poisoned".to_string());
+    };
+    client.get_or_insert_with(build_http_client).clone()
+}
+
+/// Proxies for outbound requests, from the settings or else the usual
+/// environment variables
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ProxyConfig {
+    pub http: Option<String>,
+    pub https: Option<String>,
+    pub no_proxy: Option<String>,
+}
+
+// Always bypass the proxies, so requests to the backend never leave the machine
+const LOOPBACK_NO_PROXY: &str = "127.0.0.1,::1,localhost";
+
+// The proxy settings with their env fallbacks, applied by `apply_settings`
+static PROXY_CONFIG: Mutex<ProxyConfig> = Mutex::new(ProxyConfig { http: None, https: None, no_proxy: None });
+
+impl ProxyConfig {
+    fn from_settings(settings: &Settings) -> Self {
+        // An empty setting counts as unset
+        let pick = |setting: &Option<String>, vars: [&str; 2]| {
+            setting.clone()
+                .filter(|value| !value.trim().is_empty())
+                .or_else(|| vars.into_iter().find_map(|var| std::env::var(var).ok()))
+                .filter(|value| !value.trim().is_empty())
+        };
+        Self {
+            http: pick(&settings.http_proxy, ["HTTP_PROXY", "http_proxy"]),
+            https: pick(&settings.https_proxy, ["HTTPS_PROXY", "https_proxy"]),
+            no_proxy: pick(&settings.no_proxy, ["NO_PROXY", "no_proxy"]),
+        }
+    }
+
+    /// The proxy a request to `url` goes through, if any
+    fn for_url(&self, url: &str) -> Option<&str> {
+        if url.starts_with("https://") { self.https.as_deref() } else { self.http.as_deref() }
+    }
+}
+
+/// Build the shared client with the configured proxies
+///
+/// Without any, reqwest's own system proxy detection applies. An invalid
+/// proxy is skipped with a warning rather than cutting off the backend too.
+fn build_http_client() -> Result<reqwest::Client, String> {
+    let config = PROXY_CONFIG.lock().map(|c| c.clone()).unwrap_or_default();
+    let mut builder = reqwest::Client::builder();
+    let no_proxy = match &config.no_proxy {
+        Some(hosts) => format!("{},{}", hosts, LOOPBACK_NO_PROXY),
+        None => LOOPBACK_NO_PROXY.to_string(),
+    };
+    let proxies: [(&Option<String>, fn(&str) -> reqwest::Result<reqwest::Proxy>); 2] = [
+        (&config.http, |url| reqwest::Proxy::http(url)),
+        (&config.https, |url| reqwest::Proxy::https(url)),
+    ];
+    for (url, make_proxy) in proxies {
+        let Some(url) = url else { continue };
+        match make_proxy(url) {
+            Ok(proxy) => builder = builder.proxy(proxy.no_proxy(reqwest::NoProxy::from_string(&no_proxy))),
+            Err(e) => eprintln!("Warning: Ignoring invalid proxy '{}': {}", url, e),
+        }
+    }
+    builder.build().map_err(|e| format!("Failed to initialise networking: {}", e))
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProxyTest {
+    pub url: String,
+    /// The proxy the request went through; None means a direct connection
+    pub proxy: Option<String>,
+    pub status: u16,
+    pub elapsed_ms: u64,
+}
+
+/// Check that outbound requests get through, by sending a HEAD request to
+/// `url` (by default the update server) the way every other request goes
+#[tauri::command]
+async fn test_proxy(app: tauri::AppHandle, url: Option<String>) -> Result<ProxyTest, String> {
+    let url = match url.filter(|u| !u.trim().is_empty()) {
+        Some(url) => url,
+        None => resolve_update_endpoint(&app, false).await?,
+    };
+    let proxy = PROXY_CONFIG.lock().ok().and_then(|c| c.for_url(&url).map(str::to_string));
+    let started = std::time::Instant::now();
+    let resp = http_client()?
+        .head(&url)
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await
+        .map_err(|e| match &proxy {
+            Some(proxy) => format!("Failed to reach {} through proxy {}: {}", url, proxy, e),
+            None => format!("Failed to reach {} (no proxy configured): {}", url, e),
+        })?;
+    Ok(ProxyTest {
+        url,
+        proxy,
+        status: resp.status().as_u16(),
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+// The `endpoints` setting, applied by `apply_settings`; None until then
+static BACKEND_ENDPOINTS: Mutex<Option<BackendEndpoints>> = Mutex::new(None);
+
+/// Full URL of a configurable control API endpoint
+fn endpoint_url(path: fn(&BackendEndpoints) -> &str) -> String {
+    endpoint_url_on(CONTROL_PORT.load(Ordering::Relaxed), path)
+}
+
+/// Full URL of a configurable endpoint on the control API at `port`, e.g. a
+/// secondary instance's
+fn endpoint_url_on(port: u16, path: fn(&BackendEndpoints) -> &str) -> String {
+    let endpoints = BACKEND_ENDPOINTS.lock().ok().and_then(|e| e.clone()).unwrap_or_default();
+    format!("http://127.0.0.1:{}{}", port, path(&endpoints))
+}
+
+// Last status received from the backend, shared between the poller and commands
+static LAST_STATUS: Mutex<Option<AppStatus>> = Mutex::new(None);
+
+// Version/capabilities reported by the connected backend, if any
+static BACKEND_INFO: Mutex<Option<BackendVersion>> = Mutex::new(None);
+
+// Set while the tray shows the cached status from the previous run
+static TRAY_STALE: AtomicBool = AtomicBool::new(false);
+
+// Set once the status poller is running; setup may run more than once
+// (mobile entry points, reloads) but there must only ever be one poller
+static POLLER_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Run `start` (spawning the poller) unless it already ran in this process;
+/// returns whether it ran
+fn start_background_tasks_once(start: impl FnOnce()) -> bool {
+    if POLLER_STARTED.swap(true, Ordering::SeqCst) {
+        return false;
+    }
+    start();
+    true
+}
+
+// File (in the app cache dir) holding the last known good status
+const STATUS_CACHE_FILE: &str = "last-status.json";
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ProcessInfo {
@@ -31,6 +331,13 @@ pub struct ProcessInfo {
 struct ToolStatus {
     name: String,
     status: String,
+    // Older backends don't report this; every listed tool is enabled there
+    #[serde(default = "default_true")]
+    enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -50,17 +357,156 @@ struct AppStatus {
     profiles: Vec<ProfileStatus>,
 }
 
-fn build_tray_menu<R: tauri::Runtime>(app: &tauri::AppHandle<R>, status: &Option<AppStatus>) -> tauri::Result<Menu<R>> {
+/// Overall health derived from a status, used for the tray icon and the UI
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthState {
+    /// Gateway running and every tool ok
+    Ok,
+    /// Gateway running but no tools enabled in any running profile
+    Idle,
+    /// Gateway running but at least one tool isn't ok
+    Warning,
+    /// Gateway stopped or unreachable
+    Error,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HealthSummary {
+    pub state: HealthState,
+    pub tools_enabled_count: usize,
+}
+
+fn compute_health(status: &Option<AppStatus>) -> HealthSummary {
+    let Some(s) = status else {
+        return HealthSummary { state: HealthState::Error, tools_enabled_count: 0 };
+    };
+
+    let tools = scoped_tools(s);
+
+    let state = if !s.gateway_running {
+        HealthState::Error
+    } else if tools.is_empty() {
+        HealthState::Idle
+    } else if tools.iter().any(|ts| ts.status != "ok") {
+        HealthState::Warning
+    } else {
+        HealthState::Ok
+    };
+
+    HealthSummary { state, tools_enabled_count: tools.len() }
+}
+
+// The `icon_scope` setting, applied by `apply_settings`
+static ICON_SCOPE: Mutex<IconScope> = Mutex::new(IconScope::Running);
+// The `tray_compact_title` setting
+static TRAY_COMPACT_TITLE: AtomicBool = AtomicBool::new(false);
+
+/// Enabled tools that count towards the tray's health display
+fn scoped_tools(s: &AppStatus) -> Vec<&ToolStatus> {
+    let scope = ICON_SCOPE.lock().map(|scope| *scope).unwrap_or_default();
+    s.profiles.iter()
+        .filter(|p| p.running)
+        .filter(|p| scope == IconScope::Running || p.id == s.active_profile_id)
+        .flat_map(|p| p.tool_status.as_deref().unwrap_or_default())
+        .filter(|t| t.enabled)
+        .collect()
+}
+
+/// Compact tool counts like "4✓ 1!" (ok, warning, then other failures),
+/// leaving out zero warning/failure counts
+fn compact_tool_summary(status: &Option<AppStatus>) -> Option<String> {
+    let tools = scoped_tools(status.as_ref()?);
+    let count = |pred: fn(&str) -> bool| tools.iter().filter(|t| pred(&t.status)).count();
+    let ok = count(|s| s == "ok");
+    let warning = count(|s| s == "warning");
+    let failed = count(|s| !matches!(s, "ok" | "warning" | "idle"));
+
+    let mut summary = format!("{}✓", ok);
+    if warning > 0 {
+        summary.push_str(&format!(" {}!", warning));
+    }
+    if failed > 0 {
+        summary.push_str(&format!(" {}✗", failed));
+    }
+    Some(summary)
+}
+
+/// Health of the gateway as of the last status poll
+#[tauri::command]
+async fn get_health() -> Result<HealthSummary, String> {
+    Ok(compute_health(&last_status()))
+}
+
+// Consecutive tray menu build failures, and when to fall back to the minimal menu
+static TRAY_MENU_FAILURES: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+const TRAY_MENU_FALLBACK_AFTER: u32 = 2;
+
+/// Minimal menu used when the full one can't be built
+fn build_fallback_tray_menu<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> tauri::Result<Menu<R>> {
+    let show = MenuItem::with_id(app, "show", "Open MCP Scooter Dashboard", true, None::<&str>)?;
+    let restart = MenuItem::with_id(app, "restart", "↻ Restart Gateway", true, None::<&str>)?;
+    let separator = PredefinedMenuItem::separator(app)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit MCP Scooter", true, None::<&str>)?;
+    Menu::with_items(app, &[&show, &restart, &separator, &quit])
+}
+
+/// A tool's submenu in the tray, with its per-tool actions
+fn build_tool_submenu<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    settings: &Settings,
+    profile: &str,
+    tool: &ToolStatus,
+    label: &str,
+) -> tauri::Result<Submenu<R>> {
+    let restart_item = MenuItem::with_id(app, tool_menu_id("tool_", profile, &tool.name), "Restart", true, None::<&str>)?;
+    let enabled_item = CheckMenuItem::with_id(app, tool_menu_id("toggle_", profile, &tool.name), "Enabled", true, tool.enabled, None::<&str>)?;
+    let copy_item = MenuItem::with_id(app, tool_menu_id("copy_", profile, &tool.name), "Copy Config", true, None::<&str>)?;
+    let muted = settings.muted_tools.contains(&tool.name);
+    let mute_item = CheckMenuItem::with_id(app, tool_menu_id("mute_", profile, &tool.name), "Mute Notifications", true, muted, None::<&str>)?;
+    let error_item = MenuItem::with_id(app, tool_menu_id("error_", profile, &tool.name), "Why Is This Down?", true, None::<&str>)?;
+    let mut tool_items: Vec<&dyn tauri::menu::IsMenuItem<R>> = vec![&enabled_item, &restart_item, &copy_item, &mute_item];
+    if !matches!(tool.status.as_str(), "ok" | "idle") {
+        tool_items.insert(0, &error_item);
+    }
+    Submenu::with_id_and_items(app, tool_menu_id("toolmenu_", profile, &tool.name), label, true, &tool_items)
+}
+
+/// Tray icon for a tool status
+fn tool_status_icon(status: &str) -> &'static str {
+    match status {
+        "ok" => "🟢",      // Running/Active
+        "idle" => "⚪",    // Enabled but not running
+        "warning" => "🟡",
+        _ => "🔴",
+    }
+}
+
+/// Build the tray menu for a status
+///
+/// `stale` marks a status cached from a previous run that hasn't been
+/// confirmed by the backend yet.
+fn build_tray_menu<R: tauri::Runtime>(app: &tauri::AppHandle<R>, status: &Option<AppStatus>, stale: bool) -> tauri::Result<Menu<R>> {
     let mut items: Vec<Box<dyn tauri::menu::IsMenuItem<R>>> = Vec::new();
+    let settings = app.try_state::<SettingsState>().map(|s| s.get()).unwrap_or_default();
 
     if let Some(s) = status {
-        let gateway_text = format!("Gateway: {} (Port {})", 
+        // With secondary instances the tray groups status by instance
+        let gateway_text = format!("{}: {} (Port {}){}",
+            if settings.instances.is_empty() { "Gateway" } else { "Primary Gateway" },
             if s.gateway_running { "Running" } else { "Stopped" },
-            s.mcp_port
+            s.mcp_port,
+            if stale { " — last known, connecting..." } else { "" }
         );
         items.push(Box::new(MenuItem::with_id(app, "status_header", &gateway_text, false, None::<&str>)?));
         items.push(Box::new(PredefinedMenuItem::separator(app)?));
 
+        if let Some(backend_version) = backend_version_mismatch() {
+            let warning = format!("⚠ Backend v{} doesn't match app v{}", backend_version.trim_start_matches('v'), APP_VERSION);
+            items.push(Box::new(MenuItem::with_id(app, "version_mismatch", &warning, false, None::<&str>)?));
+            items.push(Box::new(PredefinedMenuItem::separator(app)?));
+        }
+
         let mut has_tools = false;
         
         // Show Active Profile Info
@@ -82,64 +528,394 @@ fn build_tray_menu<R: tauri::Runtime>(app: &tauri::AppHandle<R>, status: &Option
                     format!("  Profile: {}", p.id)
                 };
                 
-                items.push(Box::new(MenuItem::with_id(app, format!("profile_{}", p.id), &profile_label, false, None::<&str>)?));
+                items.push(Box::new(MenuItem::with_id(app, format!("profile_{}", menu_id_part(&p.id)), &profile_label, false, None::<&str>)?));
                 
-                for tool in tools {
-                    let icon = match tool.status.as_str() {
-                        "ok" => "🟢",      // Running/Active
-                        "idle" => "⚪",    // Enabled but not running
-                        "warning" => "🟡",
-                        _ => "🔴",
-                    };
-                    let tool_text = format!("    {} {}", icon, tool.name);
-                    items.push(Box::new(MenuItem::with_id(app, format!("tool_{}", tool.name), &tool_text, true, None::<&str>)?));
+                if settings.tray_tool_layout == TrayToolLayout::Grouped {
+                    // Problems first, healthy tools tucked away at the bottom
+                    let groups: [(&str, &str, fn(&str) -> bool); 3] = [
+                        ("failing", "Failing", |s| !matches!(s, "ok" | "warning" | "idle")),
+                        ("warnings", "Warnings", |s| s == "warning"),
+                        ("healthy", "Healthy", |s| matches!(s, "ok" | "idle")),
+                    ];
+                    for (group_id, group_name, in_group) in groups {
+                        let submenus = tools.iter()
+                            .filter(|t| in_group(&t.status))
+                            .map(|t| {
+                                let tool_text = format!("{} {}", tool_status_icon(&t.status), t.name);
+                                build_tool_submenu(app, &settings, &p.id, t, &tool_text)
+                            })
+                            .collect::<tauri::Result<Vec<_>>>()?;
+                        if submenus.is_empty() {
+                            continue;
+                        }
+                        let group_items: Vec<&dyn tauri::menu::IsMenuItem<R>> =
+                            submenus.iter().map(|m| m as &dyn tauri::menu::IsMenuItem<R>).collect();
+                        items.push(Box::new(Submenu::with_id_and_items(
+                            app,
+                            format!("toolgroup_{}_{}", group_id, menu_id_part(&p.id)),
+                            format!("    {} ({})", group_name, submenus.len()),
+                            true,
+                            &group_items,
+                        )?));
+                    }
+                } else {
+                    for tool in tools {
+                        let tool_text = format!("    {} {}", tool_status_icon(&tool.status), tool.name);
+                        items.push(Box::new(build_tool_submenu(app, &settings, &p.id, tool, &tool_text)?));
+                    }
                 }
             }
         }
         
         if !has_tools {
-            items.push(Box::new(MenuItem::with_id(app, "no_tools", "No tools enabled", false, None::<&str>)?));
+            items.push(Box::new(MenuItem::with_id(app, "no_tools", "⚪ No tools enabled", false, None::<&str>)?));
         }
         
         items.push(Box::new(PredefinedMenuItem::separator(app)?));
+        // Reloading is much less disruptive, so offer it first when available
+        if backend_supports("reload") {
+            items.push(Box::new(MenuItem::with_id(app, "reload", "⟳ Reload Config", true, None::<&str>)?));
+        }
+        if backend_supports("rescan") {
+            items.push(Box::new(MenuItem::with_id(app, "rescan_tools", "⟳ Rescan Tools", true, None::<&str>)?));
+        }
         items.push(Box::new(MenuItem::with_id(app, "restart", "↻ Restart Gateway", true, None::<&str>)?));
+        if has_tools {
+            items.push(Box::new(MenuItem::with_id(app, "claude_config", "Copy Claude Config", true, None::<&str>)?));
+        }
+        items.push(Box::new(PredefinedMenuItem::separator(app)?));
+    } else if BACKEND_CRASHED.load(Ordering::SeqCst) {
+        items.push(Box::new(MenuItem::with_id(app, "start_backend", "Gateway crashed — Start", true, None::<&str>)?));
+        items.push(Box::new(PredefinedMenuItem::separator(app)?));
+    } else if BACKEND_STOPPED.load(Ordering::SeqCst) {
+        items.push(Box::new(MenuItem::with_id(app, "start_backend", "Gateway stopped — Start", true, None::<&str>)?));
         items.push(Box::new(PredefinedMenuItem::separator(app)?));
     } else {
         items.push(Box::new(MenuItem::with_id(app, "gateway_status", "Connecting to Gateway...", false, None::<&str>)?));
         items.push(Box::new(PredefinedMenuItem::separator(app)?));
     }
 
+    for instance in &settings.instances {
+        let info = instance_info(instance);
+        let icon = match (info.phase, info.health.state) {
+            (BackendPhase::Running, HealthState::Ok | HealthState::Idle) => "🟢",
+            (BackendPhase::Running, HealthState::Warning) => "🟡",
+            (BackendPhase::Starting, _) => "⚪",
+            _ => "🔴",
+        };
+        let label = format!("{} {}: {} (Port {})", icon, info.name, phase_label(info.phase), info.mcp_port);
+        let running = info.pid.is_some();
+        let tools_text = format!("{} tools enabled", info.health.tools_enabled_count);
+        let tools_item = MenuItem::with_id(app, instance_menu_id("tools", &instance.id), &tools_text, false, None::<&str>)?;
+        let start_item = MenuItem::with_id(app, instance_menu_id("start", &instance.id), "Start", !running, None::<&str>)?;
+        let stop_item = MenuItem::with_id(app, instance_menu_id("stop", &instance.id), "Stop", running, None::<&str>)?;
+        let restart_item = MenuItem::with_id(app, instance_menu_id("restart", &instance.id), "↻ Restart", running, None::<&str>)?;
+        items.push(Box::new(Submenu::with_id_and_items(
+            app,
+            instance_menu_id("menu", &instance.id),
+            &label,
+            true,
+            &[&tools_item, &start_item, &stop_item, &restart_item],
+        )?));
+    }
+    if !settings.instances.is_empty() {
+        items.push(Box::new(PredefinedMenuItem::separator(app)?));
+    }
+
+    items.push(Box::new(CheckMenuItem::with_id(app, "restart_on_crash", "Restart on Crash", true, settings.restart_on_crash, None::<&str>)?));
+    items.push(Box::new(CheckMenuItem::with_id(app, "always_on_top", "Keep window on top", true, settings.always_on_top, None::<&str>)?));
+
     items.push(Box::new(MenuItem::with_id(app, "show", "Open MCP Scooter Dashboard", true, None::<&str>)?));
+    items.push(Box::new(MenuItem::with_id(app, "recent_logs", "Copy Recent Logs", true, None::<&str>)?));
+    items.push(Box::new(MenuItem::with_id(app, "report_bug", "Report a Bug...", true, None::<&str>)?));
     items.push(Box::new(MenuItem::with_id(app, "quit", "Quit MCP Scooter", true, None::<&str>)?));
 
     let ref_items: Vec<&dyn tauri::menu::IsMenuItem<R>> = items.iter().map(|i| i.as_ref()).collect();
     Menu::with_items(app, &ref_items)
 }
 
+/// Build a control API URL from path segments, percent-encoding each one
+fn control_url(segments: &[&str]) -> Result<url::Url, String> {
+    let mut url = url::Url::parse(&control_api()).map_err(|e| format!("Invalid control API URL: {}", e))?;
+    url.path_segments_mut()
+        .map_err(|_| "Invalid control API URL".to_string())?
+        .pop_if_empty()
+        .extend(segments);
+    Ok(url)
+}
+
+/// Response of the backend's /api/version endpoint
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BackendVersion {
+    pub version: String,
+    /// Optional capabilities, e.g. "reload"
+    #[serde(default)]
+    pub features: Vec<String>,
+}
+
+/// Ask the backend for its version and capabilities
+async fn fetch_backend_version(client: &reqwest::Client) -> Option<BackendVersion> {
+    let resp = client.get(format!("{}/api/version", control_api())).send().await.ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
+    resp.json::<BackendVersion>().await.ok()
+}
+
+// Version of this desktop build; the bundled backend is released in lockstep
+const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct VersionMismatch {
+    pub app_version: String,
+    pub backend_version: String,
+}
+
+/// (major, minor) of a version string like "1.2.3", "v1.2.3" or "1.2.3-beta.1"
+fn major_minor(version: &str) -> Option<(u64, u64)> {
+    let mut parts = version.trim_start_matches('v').split(['.', '-', '+']);
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().and_then(|m| m.parse().ok()).unwrap_or(0);
+    Some((major, minor))
+}
+
+/// Whether two versions are API compatible: same major, or for 0.x releases
+/// (where minor bumps may break) the same minor as well
+fn versions_compatible(a: &str, b: &str) -> bool {
+    match (major_minor(a), major_minor(b)) {
+        (Some((a_major, a_minor)), Some((b_major, b_minor))) => {
+            a_major == b_major && (a_major != 0 || a_minor == b_minor)
+        }
+        // Can't tell; don't raise a false alarm
+        _ => true,
+    }
+}
+
+/// Backend version, if the connected backend is incompatible with this app
+fn backend_version_mismatch() -> Option<String> {
+    let info = BACKEND_INFO.lock().ok()?.clone()?;
+    (!versions_compatible(APP_VERSION, &info.version)).then_some(info.version)
+}
+
+/// Re-negotiate the backend version, e.g. after it (re)connected
+///
+/// A backend from a different release line usually means a packaging
+/// mistake, so that is reported via a `version-mismatch` event.
+async fn refresh_backend_version<R: tauri::Runtime>(handle: &tauri::AppHandle<R>, client: &reqwest::Client) {
+    let version = fetch_backend_version(client).await;
+    if let Ok(mut info) = BACKEND_INFO.lock() {
+        *info = version;
+    }
+    if let Some(backend_version) = backend_version_mismatch() {
+        eprintln!("Warning: Backend version {} doesn't match app version {}", backend_version, APP_VERSION);
+        let _ = handle.emit("version-mismatch", VersionMismatch {
+            app_version: APP_VERSION.to_string(),
+            backend_version,
+        });
+    }
+}
+
+/// Whether the connected backend advertised a capability
+fn backend_supports(feature: &str) -> bool {
+    BACKEND_INFO.lock()
+        .map(|info| info.as_ref().is_some_and(|v| v.features.iter().any(|f| f == feature)))
+        .unwrap_or(false)
+}
+
+/// Ask the backend to re-read profiles and tools without dropping connections
 #[tauri::command]
-async fn check_port_usage(port: u16) -> Result<Option<ProcessInfo>, String> {
+async fn reload_gateway_config(app: tauri::AppHandle) -> Result<(), String> {
+    let resp = http_client()?.post(format!("{}/api/reload", control_api())).send().await
+        .map_err(|e| format!("Failed to reach backend: {}", e))?;
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err("This backend version doesn't support reloading its config; restart the gateway instead".to_string());
+    }
+    if !resp.status().is_success() {
+        return Err(format!("Backend returned {}", resp.status()));
+    }
+    refresh_status(&app).await;
+    Ok(())
+}
+
+/// Tools that appeared or disappeared in a rescan, as "profile/tool"
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RescanSummary {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// Every listed tool in a status, as "profile/tool"
+fn tool_keys(status: &AppStatus) -> std::collections::BTreeSet<String> {
+    status.profiles.iter()
+        .flat_map(|p| p.tool_status.iter().flatten().map(move |t| format!("{}/{}", p.id, t.name)))
+        .collect()
+}
+
+/// Ask the backend to rescan its tool configuration, picking up newly
+/// installed MCP servers, then refresh the tray and report what changed
+#[tauri::command]
+async fn rescan_tools(app: tauri::AppHandle) -> Result<RescanSummary, String> {
+    let client = http_client()?;
+    let before = fetch_status(&client).await;
+    let resp = client.post(format!("{}/api/tools/rescan", control_api())).send().await
+        .map_err(|e| format!("Failed to reach backend: {}", e))?;
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err("This backend version doesn't support rescanning tools".to_string());
+    }
+    if !resp.status().is_success() {
+        return Err(format!("Backend returned {}", resp.status()));
+    }
+
+    // The rescan request returns once the backend has finished
+    let after = fetch_status(&client).await;
+    let before_keys = before.as_ref().map(tool_keys).unwrap_or_default();
+    let after_keys = after.as_ref().map(tool_keys).unwrap_or_default();
+    apply_status(&app, after);
+
+    let summary = RescanSummary {
+        added: after_keys.difference(&before_keys).cloned().collect(),
+        removed: before_keys.difference(&after_keys).cloned().collect(),
+    };
+    let body = match (summary.added.len(), summary.removed.len()) {
+        (0, 0) => "No tools were added or removed".to_string(),
+        (added, removed) => format!("{} tool(s) added, {} removed", added, removed),
+    };
+    notify(&app, NotificationUrgency::Low, "Tool rescan complete", &body);
+    Ok(summary)
+}
+
+/// Why a status fetch failed
+#[derive(Debug, Clone, PartialEq)]
+enum StatusError {
+    /// Connection failed (backend not running or not listening yet)
+    Unreachable,
+    /// Backend answered with a non-success HTTP status
+    Http(u16),
+    /// Response body wasn't a valid status
+    InvalidBody,
+}
+
+/// How much the desktop layer reports on the `debug-event` channel
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum EventVerbosity {
+    Off = 0,
+    /// Internal state transitions (lifecycle, update state, status changes)
+    Transitions = 1,
+    /// Also every poll's outcome and timing
+    Polls = 2,
+    /// Also every poll's raw status body
+    Raw = 3,
+}
+
+static EVENT_VERBOSITY: std::sync::atomic::AtomicU8 = std::sync::atomic::AtomicU8::new(EventVerbosity::Off as u8);
+
+// Where debug events go; set once the app is set up
+static DEBUG_EVENT_HANDLE: std::sync::OnceLock<tauri::AppHandle> = std::sync::OnceLock::new();
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DebugEvent {
+    pub kind: String,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub detail: serde_json::Value,
+}
+
+/// Emit a `debug-event` if the verbosity is at least `level`
+///
+/// `detail` is only built when the event is emitted, so normal operation
+/// pays nothing for it.
+fn debug_event(level: EventVerbosity, kind: &str, detail: impl FnOnce() -> serde_json::Value) {
+    if EVENT_VERBOSITY.load(Ordering::Relaxed) < level as u8 {
+        return;
+    }
+    if let Some(handle) = DEBUG_EVENT_HANDLE.get() {
+        let _ = handle.emit("debug-event", DebugEvent { kind: kind.to_string(), timestamp: unix_now(), detail: detail() });
+    }
+}
+
+/// Choose how much is emitted on the `debug-event` channel, for tracing why
+/// the UI isn't updating
+#[tauri::command]
+async fn set_event_verbosity(level: EventVerbosity) -> Result<(), String> {
+    EVENT_VERBOSITY.store(level as u8, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Fetch the full status from the backend
+async fn try_fetch_status(client: &reqwest::Client) -> Result<AppStatus, StatusError> {
+    let started = std::time::Instant::now();
+    let mut body = None;
+    let result = async {
+        let resp = client.get(endpoint_url(|e| e.status.as_str())).send().await
+            .map_err(|_| StatusError::Unreachable)?;
+        if !resp.status().is_success() {
+            return Err(StatusError::Http(resp.status().as_u16()));
+        }
+        let text = resp.text().await.map_err(|_| StatusError::Unreachable)?;
+        let parsed = serde_json::from_str::<AppStatus>(&text).map_err(|_| StatusError::InvalidBody);
+        body = Some(text);
+        parsed
+    }.await;
+
+    debug_event(EventVerbosity::Polls, "poll", || {
+        let mut detail = serde_json::json!({
+            "elapsed_ms": started.elapsed().as_millis() as u64,
+            "outcome": match &result {
+                Ok(_) => "ok".to_string(),
+                Err(e) => format!("{:?}", e),
+            },
+        });
+        if EVENT_VERBOSITY.load(Ordering::Relaxed) >= EventVerbosity::Raw as u8 {
+            detail["body"] = serde_json::json!(body);
+        }
+        detail
+    });
+    result
+}
+
+/// Fetch the full status from the backend, or None if it's unreachable
+async fn fetch_status(client: &reqwest::Client) -> Option<AppStatus> {
+    try_fetch_status(client).await.ok()
+}
+
+/// Lightweight liveness check that avoids transferring the full status
+async fn ping_backend(client: &reqwest::Client) -> bool {
+    ping_backend_on(client, CONTROL_PORT.load(Ordering::Relaxed)).await
+}
+
+/// Liveness check of the backend whose control API is on `port`
+async fn ping_backend_on(client: &reqwest::Client, port: u16) -> bool {
+    match client.head(endpoint_url_on(port, |e| e.ping.as_str())).send().await {
+        Ok(resp) => resp.status().is_success(),
+        Err(_) => false,
+    }
+}
+
+/// Find the pids of all processes listening on a port (IPv4 or IPv6)
+fn find_port_holders(port: u16) -> Result<Vec<u32>, String> {
+    let mut pids: Vec<u32> = Vec::new();
+
     #[cfg(target_os = "windows")]
     {
-        let output = Command::new("cmd")
-            .args(&["/C", &format!("netstat -ano | findstr :{}", port)])
+        // Run netstat directly (no cmd.exe / findstr) and filter here
+        let output = background_command("netstat")
+            .args(&["-ano"])
             .output()
             .map_err(|e| e.to_string())?;
 
+        let suffix = format!(":{}", port);
         let stdout = String::from_utf8_lossy(&output.stdout);
         for line in stdout.lines() {
             if line.contains("LISTENING") {
+                // Proto, Local Address, Foreign Address, State, PID
+                // The local address is either "0.0.0.0:6200" or "[::]:6200"
                 let parts: Vec<&str> = line.split_whitespace().collect();
-                if let Some(pid_str) = parts.last() {
-                    if let Ok(pid) = pid_str.parse::<u32>() {
-                        let mut sys = System::new_all();
-                        sys.refresh_all();
-                        if let Some(process) = sys.process(Pid::from(pid as usize)) {
-                            return Ok(Some(ProcessInfo {
-                                pid,
-                                name: process.name().to_string_lossy().to_string(),
-                            }));
-                        }
-                    }
+                if parts.len() < 5 || parts[0] != "TCP" || !parts[1].ends_with(&suffix) {
+                    continue;
+                }
+                if let Some(pid) = parts.last().and_then(|p| p.parse::<u32>().ok()) {
+                    pids.push(pid);
                 }
             }
         }
@@ -147,261 +923,5573 @@ async fn check_port_usage(port: u16) -> Result<Option<ProcessInfo>, String> {
 
     #[cfg(not(target_os = "windows"))]
     {
-        let output = Command::new("lsof")
-            .args(&["-ti", &format!(":{}", port)])
+        let output = background_command("lsof")
+            .args(&["-ti", &format!("tcp:{}", port), "-sTCP:LISTEN"])
             .output()
             .map_err(|e| e.to_string())?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
-        if let Some(pid_str) = stdout.lines().next() {
-            if let Ok(pid) = pid_str.parse::<u32>() {
-                let mut sys = System::new_all();
-                sys.refresh_all();
-                if let Some(process) = sys.process(Pid::from(pid as usize)) {
-                    return Ok(Some(ProcessInfo {
-                        pid,
-                        name: process.name().to_string_lossy().to_string(),
-                    }));
-                }
-            }
-        }
+        pids.extend(stdout.lines().filter_map(|l| l.trim().parse::<u32>().ok()));
     }
 
-    Ok(None)
+    pids.sort_unstable();
+    pids.dedup();
+    Ok(pids)
 }
 
-#[tauri::command]
-async fn kill_process(pid: u32) -> Result<bool, String> {
-    let mut sys = System::new_all();
-    sys.refresh_all();
-    if let Some(process) = sys.process(Pid::from(pid as usize)) {
-        return Ok(process.kill());
-    }
-    Err("Process not found".to_string())
+/// Processes that must never be killed from the UI: ourselves and the OS
+/// core processes (init/launchd on Unix, System/Idle on Windows)
+fn is_protected_pid(pid: u32) -> bool {
+    pid == std::process::id() || pid <= 4
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct UpdateInfo {
-    pub available: bool,
-    pub version: Option<String>,
-    pub notes: Option<String>,
-    pub date: Option<String>,
+/// Snapshot of the last status received from the backend
+fn last_status() -> Option<AppStatus> {
+    LAST_STATUS.lock().ok().and_then(|s| s.clone())
 }
 
-/// Check for updates using the appropriate channel (stable or beta)
-/// 
-/// The updater endpoints:
-/// - Stable: https://github.com/mcp-scooter/scooter/releases/download/updater/latest.json
-/// - Beta: https://github.com/mcp-scooter/scooter/releases/download/updater/beta.json
-#[tauri::command]
-async fn check_for_updates(app: tauri::AppHandle, include_beta: bool) -> Result<UpdateInfo, String> {
-    let endpoint = if include_beta {
-        "https://github.com/mcp-scooter/scooter/releases/download/updater/beta.json"
-    } else {
-        "https://github.com/mcp-scooter/scooter/releases/download/updater/latest.json"
-    };
-    
-    // Create a custom updater with the appropriate endpoint
-    let updater = app.updater_builder()
-        .endpoints(vec![endpoint.parse().map_err(|e: url::ParseError| format!("Invalid URL: {}", e))?])
-        .map_err(|e| format!("Failed to set endpoints: {}", e))?
-        .build()
-        .map_err(|e| format!("Failed to build updater: {}", e))?;
-    
-    match updater.check().await {
-        Ok(Some(update)) => {
-            Ok(UpdateInfo {
-                available: true,
-                version: Some(update.version.clone()),
-                notes: update.body.clone(),
-                date: update.date.map(|d: time::OffsetDateTime| d.to_string()),
+/// Check if status changed (simple check)
+fn status_changed(status: &Option<AppStatus>, last_status: &Option<AppStatus>) -> bool {
+    match (status, last_status) {
+        (Some(s), Some(ls)) => {
+            s.gateway_running != ls.gateway_running || 
+            s.active_profile_id != ls.active_profile_id ||
+            s.profiles.len() != ls.profiles.len() ||
+            s.profiles.iter().any(|p| {
+                ls.profiles.iter().find(|lp| lp.id == p.id)
+                    .map(|lp| lp.active_tools != p.active_tools || lp.tool_status != p.tool_status)
+                    .unwrap_or(true)
             })
+        },
+        (None, None) => false,
+        _ => true,
+    }
+}
+
+// When the backend stopped answering, cleared once it answers again or is
+// restarted; shown in the tray tooltip
+static OFFLINE_SINCE: Mutex<Option<std::time::Instant>> = Mutex::new(None);
+
+/// Start or clear the offline timer for a poll result
+fn track_offline(reachable: bool) {
+    if let Ok(mut since) = OFFLINE_SINCE.lock() {
+        if reachable {
+            *since = None;
+        } else if since.is_none() {
+            *since = Some(std::time::Instant::now());
         }
-        Ok(None) => {
-            Ok(UpdateInfo {
-                available: false,
-                version: None,
-                notes: None,
+    }
+}
+
+// How long to wait for further status changes before rebuilding the tray
+// menu, from the `tray_debounce_ms` setting
+static TRAY_DEBOUNCE_MS: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(500);
+// Set while a debounced menu rebuild is waiting to run
+static TRAY_REBUILD_PENDING: AtomicBool = AtomicBool::new(false);
+
+/// Rebuild the tray menu from `LAST_STATUS` once the debounce delay passes
+///
+/// Tools flip status in quick succession while the backend starts, so
+/// changes arriving while a rebuild is pending are folded into it. The delay
+/// runs from the first change and isn't extended, so the menu is never more
+/// than one delay behind.
+fn schedule_tray_rebuild<R: tauri::Runtime>(handle: &tauri::AppHandle<R>) {
+    let delay = TRAY_DEBOUNCE_MS.load(Ordering::Relaxed);
+    if delay == 0 {
+        let status = LAST_STATUS.lock().map(|s| s.clone()).unwrap_or(None);
+        update_tray(handle, &status);
+        return;
+    }
+    if TRAY_REBUILD_PENDING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let handle = handle.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+        TRAY_REBUILD_PENDING.store(false, Ordering::SeqCst);
+        let status = LAST_STATUS.lock().map(|s| s.clone()).unwrap_or(None);
+        update_tray(&handle, &status);
+    });
+}
+
+/// Notify about tools that stopped or started working between two statuses
+///
+/// Moves to and from "idle" are normal and don't notify. Tools named in the
+/// `muted_tools` setting never notify; the tray still shows their status.
+fn notify_tool_transitions<R: tauri::Runtime>(handle: &tauri::AppHandle<R>, before: &AppStatus, after: &AppStatus) {
+    let settings = handle.try_state::<SettingsState>().map(|s| s.get()).unwrap_or_default();
+    let (muted, urgency) = (settings.muted_tools, settings.tool_notification_urgency);
+    for profile in &after.profiles {
+        let Some(old_profile) = before.profiles.iter().find(|p| p.id == profile.id) else {
+            continue;
+        };
+        for tool in profile.tool_status.iter().flatten() {
+            if muted.contains(&tool.name) {
+                continue;
+            }
+            let Some(old) = old_profile.tool_status.iter().flatten().find(|t| t.name == tool.name) else {
+                continue;
+            };
+            if old.status == tool.status {
+                continue;
+            }
+            let failed = old.status == "ok" && tool.status != "idle";
+            let recovered = tool.status == "ok" && old.status != "idle";
+            if failed {
+                notify(handle, urgency, "Tool problem", &format!("{} ({}) is now {}", tool.name, profile.id, tool.status));
+            } else if recovered {
+                notify(handle, NotificationUrgency::Low, "Tool recovered", &format!("{} ({}) is working again", tool.name, profile.id));
+            }
+        }
+    }
+}
+
+/// Record a freshly fetched status and refresh the tray if it changed
+fn apply_status<R: tauri::Runtime>(handle: &tauri::AppHandle<R>, status: Option<AppStatus>) {
+    track_offline(status.is_some());
+    let (changed, previous) = match LAST_STATUS.lock() {
+        Ok(mut last) => {
+            let changed = status_changed(&status, &last);
+            let previous = if changed { std::mem::replace(&mut *last, status.clone()) } else { None };
+            (changed, previous)
+        }
+        Err(_) => (false, None),
+    };
+    if changed {
+        debug_event(EventVerbosity::Transitions, "status-changed", || serde_json::json!(status));
+    }
+    if let (Some(before), Some(after)) = (&previous, &status) {
+        notify_tool_transitions(handle, before, after);
+    }
+
+    // The first live result always replaces a cached menu, even if the
+    // backend is still unreachable
+    let was_stale = TRAY_STALE.swap(false, Ordering::SeqCst);
+    // Keep retrying a menu that failed to build until it succeeds
+    let retry = TRAY_MENU_FAILURES.load(Ordering::SeqCst) > 0;
+
+    if changed || was_stale || retry {
+        schedule_tray_rebuild(handle);
+    }
+    // Every result counts towards the icon's hysteresis, changed or not
+    update_tray_icon(handle, &status);
+    if changed {
+        if let Some(s) = &status {
+            save_cached_status(handle, s);
+        }
+    }
+}
+
+/// The app cache dir, inside the portable data dir in portable mode
+fn app_cache_dir<R: tauri::Runtime>(handle: &tauri::AppHandle<R>) -> Option<PathBuf> {
+    portable::cache_dir().or_else(|| handle.path().app_cache_dir().ok())
+}
+
+/// Load the status persisted by a previous run, if any
+fn load_cached_status<R: tauri::Runtime>(handle: &tauri::AppHandle<R>) -> Option<AppStatus> {
+    let path = app_cache_dir(handle)?.join(STATUS_CACHE_FILE);
+    let text = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// Persist the last known good status so the next launch can show it immediately
+fn save_cached_status<R: tauri::Runtime>(handle: &tauri::AppHandle<R>, status: &AppStatus) {
+    let Some(dir) = app_cache_dir(handle) else {
+        return;
+    };
+    let result = std::fs::create_dir_all(&dir)
+        .and_then(|_| serde_json::to_vec(status).map_err(std::io::Error::other))
+        .and_then(|bytes| std::fs::write(dir.join(STATUS_CACHE_FILE), bytes));
+    if let Err(e) = result {
+        eprintln!("Warning: Failed to cache status: {}", e);
+    }
+}
+
+/// Fetch the status right away instead of waiting for the next poll
+async fn refresh_status<R: tauri::Runtime>(handle: &tauri::AppHandle<R>) {
+    let Ok(client) = http_client() else { return };
+    let status = fetch_status(&client).await;
+    apply_status(handle, status);
+}
+
+/// Rebuild the tray menu for a status
+fn update_tray<R: tauri::Runtime>(handle: &tauri::AppHandle<R>, status: &Option<AppStatus>) {
+    if let Some(tray) = handle.tray_by_id("main-tray") {
+        // Update menu
+        match build_tray_menu(handle, status, false) {
+            // Setting the menu can fail transiently (e.g. during a theme
+            // change); the failure count makes the next poll retry it
+            Ok(new_menu) => match tray.set_menu(Some(new_menu)) {
+                Ok(_) => TRAY_MENU_FAILURES.store(0, Ordering::SeqCst),
+                Err(e) => {
+                    let failures = TRAY_MENU_FAILURES.fetch_add(1, Ordering::SeqCst) + 1;
+                    eprintln!("Error: Failed to set tray menu (attempt {}): {}", failures, e);
+                    report_tray_menu_failure(handle, failures, &e.to_string());
+                }
+            },
+            Err(e) => {
+                let failures = TRAY_MENU_FAILURES.fetch_add(1, Ordering::SeqCst) + 1;
+                eprintln!("Error: Failed to build tray menu (attempt {}): {}", failures, e);
+                report_tray_menu_failure(handle, failures, &e.to_string());
+                // Don't leave a stale menu forever; keep at least the basics usable
+                if failures >= TRAY_MENU_FALLBACK_AFTER {
+                    match build_fallback_tray_menu(handle) {
+                        Ok(menu) => {
+                            let _ = tray.set_menu(Some(menu));
+                        }
+                        Err(e) => eprintln!("Error: Failed to build fallback tray menu: {}", e),
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Consecutive tray menu failures after which the UI is told the tray is stale
+const TRAY_MENU_ERROR_AFTER: u32 = 3;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TrayMenuError {
+    pub attempts: u32,
+    pub error: String,
+}
+
+/// Emit `tray-menu-error` once when tray menu updates keep failing
+fn report_tray_menu_failure<R: tauri::Runtime>(handle: &tauri::AppHandle<R>, failures: u32, error: &str) {
+    if failures == TRAY_MENU_ERROR_AFTER {
+        let _ = handle.emit("tray-menu-error", TrayMenuError { attempts: failures, error: error.to_string() });
+    }
+}
+
+/// Icon file for a health state
+fn tray_icon_name(state: HealthState) -> &'static str {
+    match state {
+        HealthState::Ok | HealthState::Idle => "tray-ok.png",
+        HealthState::Warning => "tray-warning.png",
+        HealthState::Error => "tray-error.png",
+    }
+}
+
+// Polls a worse state must be seen in a row before the icon shows it
+const ICON_DOWNGRADE_POLLS: u32 = 2;
+
+/// Smooths the tray icon so a flapping tool doesn't switch it every poll
+///
+/// A worse state is only shown once it has been seen `ICON_DOWNGRADE_POLLS`
+/// times in a row; a better one is shown right away.
+struct IconHysteresis {
+    shown: Option<HealthState>,
+    pending: Option<HealthState>,
+    pending_polls: u32,
+}
+
+impl IconHysteresis {
+    fn severity(state: HealthState) -> u8 {
+        match state {
+            HealthState::Ok | HealthState::Idle => 0,
+            HealthState::Warning => 1,
+            HealthState::Error => 2,
+        }
+    }
+
+    /// Feed one observed state and return the state to show
+    fn observe(&mut self, state: HealthState) -> HealthState {
+        let shown = match self.shown {
+            Some(shown) if Self::severity(state) > Self::severity(shown) => shown,
+            // First observation or not worse: show it immediately
+            _ => {
+                self.shown = Some(state);
+                self.pending = None;
+                self.pending_polls = 0;
+                return state;
+            }
+        };
+        if self.pending == Some(state) {
+            self.pending_polls += 1;
+        } else {
+            self.pending = Some(state);
+            self.pending_polls = 1;
+        }
+        if self.pending_polls < ICON_DOWNGRADE_POLLS {
+            return shown;
+        }
+        self.shown = Some(state);
+        self.pending = None;
+        self.pending_polls = 0;
+        state
+    }
+}
+
+static TRAY_ICON_STATE: Mutex<IconHysteresis> = Mutex::new(IconHysteresis {
+    shown: None,
+    pending: None,
+    pending_polls: 0,
+});
+
+// Icon file last set on the tray, so the icon is only replaced when it changes
+static APPLIED_TRAY_ICON: Mutex<Option<&'static str>> = Mutex::new(None);
+
+/// Feed a polled status into the tray icon, switching icons only when the
+/// smoothed state changes
+fn update_tray_icon<R: tauri::Runtime>(handle: &tauri::AppHandle<R>, status: &Option<AppStatus>) {
+    let observed = compute_health(status).state;
+    let shown = match TRAY_ICON_STATE.lock() {
+        Ok(mut hysteresis) => hysteresis.observe(observed),
+        Err(_) => observed,
+    };
+    let icon_name = tray_icon_name(shown);
+    let mut applied = match APPLIED_TRAY_ICON.lock() {
+        Ok(applied) => applied,
+        Err(_) => return,
+    };
+    if *applied == Some(icon_name) {
+        return;
+    }
+
+    // Until the icon is set (e.g. the tray isn't built yet), every poll retries
+    let Some(tray) = handle.tray_by_id("main-tray") else {
+        return;
+    };
+    // Load icon based on status; without it the tray keeps its current icon
+    match find_tray_icon(icon_name) {
+        Some(path) => match tauri::image::Image::from_path(&path) {
+            Ok(img) => match tray.set_icon(Some(img)) {
+                Ok(()) => *applied = Some(icon_name),
+                Err(e) => eprintln!("Warning: Failed to set tray icon {}: {}", icon_name, e),
+            },
+            Err(e) => eprintln!("Warning: Failed to load tray icon {:?}: {}", path, e),
+        },
+        None => eprintln!("Warning: Tray icon {} is missing", icon_name),
+    }
+}
+
+/// Locate a tray icon file
+fn find_tray_icon(icon_name: &str) -> Option<PathBuf> {
+    let icon_path = std::path::Path::new("icons").join(icon_name);
+    let dev_icon_path = std::path::Path::new("desktop/src-tauri/icons").join(icon_name);
+    let public_icon_path = std::path::Path::new("desktop/public/logo/icon-source.svg");
+
+    if icon_path.exists() {
+        Some(icon_path)
+    } else if dev_icon_path.exists() {
+        Some(dev_icon_path)
+    } else if public_icon_path.exists() && icon_name == "tray-ok.png" {
+        Some(public_icon_path.to_path_buf())
+    } else {
+        None
+    }
+}
+
+/// Plain square used when no icon is bundled, so the tray still works
+fn placeholder_icon() -> tauri::image::Image<'static> {
+    const SIZE: u32 = 32;
+    let rgba = [0x3b, 0x82, 0xf6, 0xff].repeat((SIZE * SIZE) as usize);
+    tauri::image::Image::new_owned(rgba, SIZE, SIZE)
+}
+
+/// Icon assets the app expects but can't find
+fn missing_assets<R: tauri::Runtime>(handle: &tauri::AppHandle<R>) -> Vec<String> {
+    let mut missing = Vec::new();
+    if handle.default_window_icon().is_none() {
+        missing.push("default window icon (bundle.icon in tauri.conf.json)".to_string());
+    }
+    for state in [HealthState::Ok, HealthState::Warning, HealthState::Error] {
+        let name = tray_icon_name(state);
+        if find_tray_icon(name).is_none() {
+            missing.push(format!("icons/{}", name));
+        }
+    }
+    missing
+}
+
+/// List icon assets that are missing from this build
+#[tauri::command]
+async fn check_assets(app: tauri::AppHandle) -> Result<Vec<String>, String> {
+    Ok(missing_assets(&app))
+}
+
+/// Make a profile/tool name safe to embed in a menu id
+fn menu_id_part(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '-' })
+        .collect()
+}
+
+/// Menu id for a per-tool item: `{prefix}{profile}_{tool}`
+fn tool_menu_id(prefix: &str, profile: &str, tool: &str) -> String {
+    format!("{}{}_{}", prefix, menu_id_part(profile), menu_id_part(tool))
+}
+
+/// Resolve a per-tool menu id back to its profile and tool
+///
+/// Names may contain underscores and are sanitized in ids, so the id is
+/// matched against the ids the last known status would produce rather than
+/// split apart.
+fn resolve_tool_menu_id(prefix: &str, id: &str) -> Option<(String, ToolStatus)> {
+    let status = last_status()?;
+    status.profiles.iter().find_map(|p| {
+        p.tool_status.as_deref().unwrap_or_default().iter()
+            .find(|t| tool_menu_id(prefix, &p.id, &t.name) == id)
+            .map(|t| (p.id.clone(), t.clone()))
+    })
+}
+
+/// Connection details the backend reports for a tool, or None if the tool
+/// has no client-facing configuration
+async fn fetch_tool_connection(profile: &str, tool: &str) -> Result<Option<serde_json::Value>, String> {
+    let url = control_url(&["api", "profiles", profile, "tools", tool, "connection"])?;
+    let resp = http_client()?.get(url).send().await
+        .map_err(|e| format!("Failed to reach backend: {}", e))?;
+    if resp.status() == reqwest::StatusCode::NOT_FOUND || resp.status() == reqwest::StatusCode::NO_CONTENT {
+        return Ok(None);
+    }
+    if !resp.status().is_success() {
+        return Err(format!("Backend returned {}", resp.status()));
+    }
+    let details: serde_json::Value = resp.json().await
+        .map_err(|e| format!("Invalid connection details: {}", e))?;
+    Ok((!details.is_null()).then_some(details))
+}
+
+/// Ready-to-paste MCP client config (`mcpServers` block) for one tool
+#[tauri::command]
+async fn tool_config_snippet(profile: String, tool: String) -> Result<String, String> {
+    let Some(details) = fetch_tool_connection(&profile, &tool).await? else {
+        return Err(format!("'{}' has no client-facing configuration to copy", tool));
+    };
+    let mut servers = serde_json::Map::new();
+    servers.insert(tool, details);
+    serde_json::to_string_pretty(&serde_json::json!({ "mcpServers": servers }))
+        .map_err(|e| e.to_string())
+}
+
+/// Top-level key each supported MCP client keeps its servers under
+fn client_servers_key(client: &str) -> Result<&'static str, String> {
+    match client.trim().to_lowercase().as_str() {
+        "claude" | "claude-desktop" | "cursor" | "windsurf" => Ok("mcpServers"),
+        // .vscode/mcp.json
+        "vscode" => Ok("servers"),
+        // Zed's settings.json
+        "zed" => Ok("context_servers"),
+        other => Err(format!(
+            "Unknown MCP client '{}'; expected claude, cursor, windsurf, vscode or zed",
+            other
+        )),
+    }
+}
+
+/// A complete client config for every enabled tool in the active profile
+///
+/// `client` picks the format (e.g. "claude" for Claude Desktop's
+/// `mcpServers` block, see `client_servers_key`). Tools without client-facing
+/// connection details are left out.
+#[tauri::command]
+async fn export_all_client_configs(client: String) -> Result<String, String> {
+    let key = client_servers_key(&client)?;
+    let status = fetch_status(&http_client()?).await.ok_or("The backend isn't reachable")?;
+    let profile = status.profiles.iter()
+        .find(|p| p.id == status.active_profile_id)
+        .ok_or("There is no active profile")?;
+
+    let mut servers = serde_json::Map::new();
+    for tool in profile.tool_status.as_deref().unwrap_or_default().iter().filter(|t| t.enabled) {
+        if let Some(details) = fetch_tool_connection(&profile.id, &tool.name).await? {
+            servers.insert(tool.name.clone(), details);
+        }
+    }
+    if servers.is_empty() {
+        return Err(format!("No enabled tool in '{}' has a client-facing configuration", profile.id));
+    }
+    let mut config = serde_json::Map::new();
+    config.insert(key.to_string(), serde_json::Value::Object(servers));
+    serde_json::to_string_pretty(&config).map_err(|e| e.to_string())
+}
+
+/// Enable or disable a tool in a profile and refresh the status
+#[tauri::command]
+async fn set_tool_enabled(app: tauri::AppHandle, profile: String, tool: String, enabled: bool) -> Result<(), String> {
+    let url = control_url(&["api", "profiles", &profile, "tools", &tool, "enabled"])?;
+    let resp = http_client()?.post(url)
+        .json(&serde_json::json!({ "enabled": enabled }))
+        .send().await
+        .map_err(|e| format!("Failed to reach backend: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("Backend returned {}", resp.status()));
+    }
+    refresh_status(&app).await;
+    Ok(())
+}
+
+/// Why a tool is down, as reported by the backend
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ToolError {
+    /// None when the tool has no recorded error, i.e. it's healthy
+    #[serde(default)]
+    pub message: Option<String>,
+    /// When the error happened (RFC 3339)
+    #[serde(default)]
+    pub timestamp: Option<String>,
+    /// How often the backend has restarted the tool
+    #[serde(default)]
+    pub restart_count: u32,
+}
+
+/// The last error the backend recorded for a tool
+///
+/// A healthy tool (or a backend without error tracking) gets a `ToolError`
+/// without a message rather than an error.
+#[tauri::command]
+async fn tool_error_detail(profile: String, tool: String) -> Result<ToolError, String> {
+    let url = control_url(&["api", "profiles", &profile, "tools", &tool, "error"])?;
+    let resp = http_client()?.get(url).send().await
+        .map_err(|e| format!("Failed to reach backend: {}", e))?;
+    if resp.status() == reqwest::StatusCode::NOT_FOUND || resp.status() == reqwest::StatusCode::NO_CONTENT {
+        return Ok(ToolError::default());
+    }
+    if !resp.status().is_success() {
+        return Err(format!("Backend returned {}", resp.status()));
+    }
+    resp.json::<Option<ToolError>>().await
+        .map(Option::unwrap_or_default)
+        .map_err(|e| format!("Invalid tool error details: {}", e))
+}
+
+/// Ask the backend to restart a single tool in a profile
+async fn restart_tool(profile: &str, tool: &str) -> Result<(), String> {
+    let url = control_url(&["api", "profiles", profile, "tools", tool, "restart"])?;
+    let resp = http_client()?.post(url).send().await
+        .map_err(|e| format!("Failed to reach backend: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("Backend returned {}", resp.status()));
+    }
+    Ok(())
+}
+
+/// Full profile metadata from /api/profiles, for on-demand detail views
+///
+/// The profile `env` map is deliberately left out since it holds tool
+/// credentials.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProfileDetail {
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub running: bool,
+    #[serde(default)]
+    pub allow_tools: Vec<String>,
+    #[serde(default)]
+    pub disabled_system_tools: Vec<String>,
+    #[serde(default)]
+    pub remote_server_url: String,
+    #[serde(default)]
+    pub remote_auth_mode: String,
+}
+
+#[derive(Deserialize)]
+struct ProfilesResponse {
+    profiles: Vec<ProfileDetail>,
+}
+
+/// List all profiles with their full metadata
+#[tauri::command]
+async fn list_profiles() -> Result<Vec<ProfileDetail>, String> {
+    let resp = http_client()?.get(format!("{}/api/profiles", control_api())).send().await
+        .map_err(|e| format!("Failed to reach backend: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("Backend returned {}", resp.status()));
+    }
+    resp.json::<ProfilesResponse>().await
+        .map(|r| r.profiles)
+        .map_err(|e| format!("Invalid profiles response: {}", e))
+}
+
+/// One problem found in a profile's config
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ValidationIssue {
+    /// The tool the problem is with, if it's tool specific
+    #[serde(default)]
+    pub tool: Option<String>,
+    pub message: String,
+}
+
+impl std::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.tool {
+            Some(tool) => write!(f, "{}: {}", tool, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ValidationReport {
+    /// False if the backend can't validate profiles; there are no issues then
+    pub supported: bool,
+    pub issues: Vec<ValidationIssue>,
+}
+
+/// Ask the backend to check a profile's config (missing binaries, bad
+/// credentials) without activating it
+#[tauri::command]
+async fn validate_profile(id: String) -> Result<ValidationReport, String> {
+    let url = control_url(&["api", "profiles", &id, "validate"])?;
+    let resp = http_client()?.post(url).send().await
+        .map_err(|e| format!("Failed to reach backend: {}", e))?;
+    if resp.status() == reqwest::StatusCode::NOT_FOUND || resp.status() == reqwest::StatusCode::METHOD_NOT_ALLOWED {
+        return Ok(ValidationReport { supported: false, issues: Vec::new() });
+    }
+    if !resp.status().is_success() {
+        return Err(format!("Backend returned {}", resp.status()));
+    }
+
+    #[derive(Deserialize)]
+    struct Body {
+        #[serde(default)]
+        issues: Vec<ValidationIssue>,
+    }
+    let body: Body = resp.json().await
+        .map_err(|e| format!("Invalid validation response: {}", e))?;
+    Ok(ValidationReport { supported: true, issues: body.issues })
+}
+
+/// Return the id of the backend's active profile
+#[tauri::command]
+async fn get_active_profile() -> Result<String, String> {
+    let client = http_client()?;
+    fetch_status(&client).await
+        .map(|s| s.active_profile_id)
+        .ok_or_else(|| "Backend is not reachable".to_string())
+}
+
+/// Switch the backend's active profile
+///
+/// The active profile is stored as `last_profile_id` in the backend
+/// settings, so this reads the settings, updates that field and writes them
+/// back untouched otherwise. The backend replaces its settings wholesale on
+/// a write and has no narrower endpoint, so a settings change made by
+/// someone else between the read and the write is lost.
+#[tauri::command]
+async fn set_active_profile(app: tauri::AppHandle, id: String, force: Option<bool>) -> Result<(), String> {
+    let client = http_client()?;
+
+    let known = match last_status() {
+        Some(s) => Some(s),
+        None => fetch_status(&client).await,
+    };
+    if let Some(status) = known {
+        if !status.profiles.iter().any(|p| p.id == id) {
+            let valid: Vec<&str> = status.profiles.iter().map(|p| p.id.as_str()).collect();
+            return Err(format!("Unknown profile '{}'. Valid profiles: {}", id, valid.join(", ")));
+        }
+    }
+
+    // Switching to a broken profile takes all its tools down; a backend that
+    // can't validate profiles just switches
+    if !force.unwrap_or(false) {
+        let report = validate_profile(id.clone()).await?;
+        if !report.issues.is_empty() {
+            let issues: Vec<String> = report.issues.iter().map(|i| i.to_string()).collect();
+            return Err(format!(
+                "Profile '{}' has problems: {}. Pass force to switch anyway",
+                id,
+                issues.join("; ")
+            ));
+        }
+    }
+
+    let settings_url = format!("{}/api/settings", control_api());
+    let resp = client.get(&settings_url).send().await
+        .map_err(|e| format!("Failed to reach backend: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("Backend returned {}", resp.status()));
+    }
+    let mut settings: serde_json::Value = resp.json().await
+        .map_err(|e| format!("Invalid settings response: {}", e))?;
+    settings.as_object_mut()
+        .ok_or("Invalid settings response: expected an object")?
+        .insert("last_profile_id".to_string(), serde_json::Value::String(id));
+
+    let resp = client.put(&settings_url).json(&settings).send().await
+        .map_err(|e| format!("Failed to reach backend: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("Backend returned {}", resp.status()));
+    }
+
+    refresh_status(&app).await;
+    Ok(())
+}
+
+// Log levels accepted by the backend's /api/log-level endpoint
+const BACKEND_LOG_LEVELS: &[&str] = &["trace", "debug", "info", "warn", "error"];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct LogLevelBody {
+    level: String,
+}
+
+/// Turn a log-level response into the level it reports
+async fn parse_log_level_response(resp: reqwest::Response) -> Result<String, String> {
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err("This backend version doesn't support changing the log level at runtime".to_string());
+    }
+    if !resp.status().is_success() {
+        return Err(format!("Backend returned {}", resp.status()));
+    }
+    resp.json::<LogLevelBody>().await
+        .map(|b| b.level)
+        .map_err(|e| format!("Invalid log level response: {}", e))
+}
+
+/// Get the backend's current log level
+#[tauri::command]
+async fn get_backend_log_level() -> Result<String, String> {
+    let resp = http_client()?.get(format!("{}/api/log-level", control_api())).send().await
+        .map_err(|e| format!("Failed to reach backend: {}", e))?;
+    parse_log_level_response(resp).await
+}
+
+/// Change the backend's log level without restarting it
+///
+/// Returns the level the backend confirmed.
+#[tauri::command]
+async fn set_backend_log_level(level: String) -> Result<String, String> {
+    let level = level.to_lowercase();
+    if !BACKEND_LOG_LEVELS.contains(&level.as_str()) {
+        return Err(format!("Invalid log level '{}'. Expected one of: {}", level, BACKEND_LOG_LEVELS.join(", ")));
+    }
+    let resp = http_client()?.post(format!("{}/api/log-level", control_api()))
+        .json(&LogLevelBody { level })
+        .send().await
+        .map_err(|e| format!("Failed to reach backend: {}", e))?;
+    parse_log_level_response(resp).await
+}
+
+#[tauri::command]
+async fn check_port_usage(port: u16) -> Result<Option<ProcessInfo>, String> {
+    let pids = find_port_holders(port)?;
+    if pids.is_empty() {
+        return Ok(None);
+    }
+
+    let mut sys = System::new_all();
+    sys.refresh_all();
+    for &pid in &pids {
+        if let Some(process) = sys.process(Pid::from(pid as usize)) {
+            return Ok(Some(ProcessInfo {
+                pid,
+                name: process.name().to_string_lossy().to_string(),
+            }));
+        }
+    }
+
+    // Something holds the port but we can't see it: most likely another user's
+    if let Some(&pid) = pids.iter().find(|&&pid| exists_without_access(pid)) {
+        return Err(access_denied_error(pid));
+    }
+    Ok(None)
+}
+
+/// Whether a pid exists but belongs to another user, which is why `sysinfo`
+/// can't see it
+///
+/// Signal 0 only checks permission; EPERM means the process exists.
+#[cfg(unix)]
+fn exists_without_access(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: signal 0 performs no action beyond the existence/permission check
+    let result = unsafe { libc::kill(pid, 0) };
+    result == -1 && std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn exists_without_access(_pid: u32) -> bool {
+    false
+}
+
+fn access_denied_error(pid: u32) -> String {
+    format!(
+        "Process {} belongs to another user and can't be inspected or killed; \
+         try again with elevated privileges (e.g. sudo)",
+        pid
+    )
+}
+
+/// Error for a pid `sysinfo` didn't find, telling "gone" from "not ours"
+fn missing_process_error(pid: u32) -> String {
+    if exists_without_access(pid) {
+        access_denied_error(pid)
+    } else {
+        "Process not found".to_string()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProcessDetails {
+    pub pid: u32,
+    pub name: String,
+    pub cmd: Vec<String>,
+    pub exe: Option<String>,
+    pub cwd: Option<String>,
+    pub parent_pid: Option<u32>,
+    /// Seconds since the Unix epoch
+    pub start_time: u64,
+    /// Whether this is the backend the app spawned
+    pub managed: bool,
+}
+
+/// Full details of a process, to tell apart processes with the same name
+/// (e.g. two `scooter` instances) before killing one
+#[tauri::command]
+async fn process_details(pid: u32) -> Result<Option<ProcessDetails>, String> {
+    let mut sys = System::new_all();
+    sys.refresh_all();
+    let Some(process) = sys.process(Pid::from(pid as usize)) else {
+        if exists_without_access(pid) {
+            return Err(access_denied_error(pid));
+        }
+        return Ok(None);
+    };
+    Ok(Some(ProcessDetails {
+        pid,
+        name: process.name().to_string_lossy().to_string(),
+        cmd: process.cmd().iter().map(|a| a.to_string_lossy().to_string()).collect(),
+        exe: process.exe().map(|p| p.to_string_lossy().to_string()),
+        cwd: process.cwd().map(|p| p.to_string_lossy().to_string()),
+        parent_pid: process.parent().map(|p| p.as_u32()),
+        start_time: process.start_time(),
+        managed: managed_backend_pid() == Some(pid),
+    }))
+}
+
+/// Resource usage of one `scooter` process
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProcessResourceUsage {
+    pub pid: u32,
+    /// Percent of one core, so it can exceed 100 on multi-core machines
+    pub cpu_percent: f32,
+    pub memory_bytes: u64,
+    pub uptime_secs: u64,
+    /// Whether this is the backend the app spawned
+    pub managed: bool,
+}
+
+/// Usage of every `scooter` process in an already refreshed `System`
+fn scooter_resources(sys: &System) -> Vec<ProcessResourceUsage> {
+    let managed = managed_backend_pid();
+    sys.processes()
+        .iter()
+        .filter(|(_, process)| process.name() == SIDECAR_NAME)
+        .map(|(pid, process)| ProcessResourceUsage {
+            pid: pid.as_u32(),
+            cpu_percent: process.cpu_usage(),
+            memory_bytes: process.memory(),
+            uptime_secs: process.run_time(),
+            managed: managed == Some(pid.as_u32()),
+        })
+        .collect()
+}
+
+/// Resource usage of every `scooter` process, managed or not
+///
+/// CPU usage needs two samples, so this takes a moment.
+#[tauri::command]
+async fn all_scooter_resources() -> Result<Vec<ProcessResourceUsage>, String> {
+    let mut sys = System::new();
+    sys.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+    tokio::time::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL).await;
+    sys.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+    Ok(scooter_resources(&sys))
+}
+
+static RESOURCE_MONITOR_RUNNING: AtomicBool = AtomicBool::new(false);
+// Bumped on every start, so a loop stopped and restarted while sleeping exits
+static RESOURCE_MONITOR_GENERATION: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+/// Emit `scooter-resources` periodically until `stop_resource_monitor`
+///
+/// Meant for while a monitoring view is open. Each cycle shares one process
+/// refresh for all processes. Does nothing if the monitor already runs.
+#[tauri::command]
+async fn start_resource_monitor(app: tauri::AppHandle, interval_ms: Option<u64>) -> Result<(), String> {
+    if RESOURCE_MONITOR_RUNNING.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+    let generation = RESOURCE_MONITOR_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let interval = Duration::from_millis(interval_ms.unwrap_or(2000)).max(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+    tauri::async_runtime::spawn(async move {
+        let mut sys = System::new();
+        sys.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+        loop {
+            tokio::time::sleep(interval).await;
+            if !RESOURCE_MONITOR_RUNNING.load(Ordering::SeqCst)
+                || RESOURCE_MONITOR_GENERATION.load(Ordering::SeqCst) != generation
+            {
+                break;
+            }
+            sys.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+            let _ = app.emit("scooter-resources", scooter_resources(&sys));
+            check_backend_memory(&app, &sys);
+        }
+    });
+    Ok(())
+}
+
+#[tauri::command]
+async fn stop_resource_monitor() -> Result<(), String> {
+    RESOURCE_MONITOR_RUNNING.store(false, Ordering::SeqCst);
+    Ok(())
+}
+
+#[tauri::command]
+async fn kill_process(pid: u32) -> Result<bool, String> {
+    if is_protected_pid(pid) {
+        return Err(format!("Refusing to kill protected process {}", pid));
+    }
+    let mut sys = System::new_all();
+    sys.refresh_all();
+    if let Some(process) = sys.process(Pid::from(pid as usize)) {
+        return Ok(process.kill());
+    }
+    Err(missing_process_error(pid))
+}
+
+/// Kill every process listening on a port, returning the pids that were killed
+///
+/// Protected processes are skipped; if nothing could be killed an error
+/// explains why.
+#[tauri::command]
+async fn kill_port_holder(port: u16) -> Result<Vec<u32>, String> {
+    let pids = find_port_holders(port)?;
+    if pids.is_empty() {
+        return Err(format!("No process is listening on port {}", port));
+    }
+
+    let mut sys = System::new_all();
+    sys.refresh_all();
+    let mut killed = Vec::new();
+    let mut skipped = Vec::new();
+    let mut denied = None;
+    for pid in pids {
+        if is_protected_pid(pid) {
+            skipped.push(pid);
+            continue;
+        }
+        match sys.process(Pid::from(pid as usize)) {
+            Some(process) => {
+                if process.kill() {
+                    killed.push(pid);
+                }
+            }
+            None if exists_without_access(pid) => denied = Some(pid),
+            None => {}
+        }
+    }
+
+    if killed.is_empty() {
+        if let Some(pid) = denied {
+            return Err(access_denied_error(pid));
+        }
+        if !skipped.is_empty() {
+            return Err(format!("Refusing to kill protected process(es) {:?} on port {}", skipped, port));
+        }
+    }
+    Ok(killed)
+}
+
+/// How an offered version relates to the installed one
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VersionChange {
+    Upgrade,
+    UpToDate,
+    /// Only offered when pinned to an older version or after a bad release
+    Downgrade,
+}
+
+/// Compare an offered version against the installed one (semver, so
+/// `0.0.10` is newer than `0.0.9` and `1.0.0-beta.2` older than `1.0.0`)
+fn version_change(current: &str, offered: &str) -> Result<VersionChange, String> {
+    let parse = |v: &str| {
+        semver::Version::parse(v.trim().trim_start_matches('v'))
+            .map_err(|e| format!("Invalid version '{}': {}", v, e))
+    };
+    Ok(match parse(offered)?.cmp(&parse(current)?) {
+        std::cmp::Ordering::Greater => VersionChange::Upgrade,
+        std::cmp::Ordering::Equal => VersionChange::UpToDate,
+        std::cmp::Ordering::Less => VersionChange::Downgrade,
+    })
+}
+
+/// Refuse to install an older version unless explicitly allowed
+fn check_downgrade(update: &tauri_plugin_updater::Update, allow_downgrade: bool) -> Result<(), String> {
+    if !allow_downgrade && version_change(&update.current_version, &update.version)? == VersionChange::Downgrade {
+        return Err(format!(
+            "Update {} is older than the installed version {}; pass allow_downgrade to install it anyway",
+            update.version, update.current_version
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UpdateInfo {
+    pub available: bool,
+    pub version: Option<String>,
+    /// How `version` compares to the installed version; None when unknown
+    /// (e.g. while another update operation runs)
+    pub version_change: Option<VersionChange>,
+    pub notes: Option<String>,
+    pub date: Option<String>,
+    pub release_url: Option<String>,
+    /// Size of the update bundle in bytes, when known
+    pub download_size: Option<u64>,
+    /// Minisign key id the update was signed with, as `minisign` prints it
+    pub signing_key_id: Option<String>,
+}
+
+const RELEASES_URL: &str = "https://github.com/mcp-scooter/scooter/releases";
+
+/// Build the GitHub release page URL for a version.
+///
+/// Stable and beta releases share the `v{version}` tag convention
+/// (e.g. `v0.0.1` and `v0.0.1-beta.8`), see .github/workflows/release.yml.
+fn release_page_url(version: &str) -> String {
+    format!("{}/tag/v{}", RELEASES_URL, version.trim_start_matches('v'))
+}
+
+/// Resolve the updater manifest URL for the selected channel
+///
+/// The updater endpoints:
+/// - Stable: https://github.com/mcp-scooter/scooter/releases/download/updater/latest.json
+/// - Beta: https://github.com/mcp-scooter/scooter/releases/download/updater/beta.json
+fn update_endpoint(include_beta: bool) -> &'static str {
+    if include_beta {
+        "https://github.com/mcp-scooter/scooter/releases/download/updater/beta.json"
+    } else {
+        "https://github.com/mcp-scooter/scooter/releases/download/updater/latest.json"
+    }
+}
+
+// Manifest listing the available update channels and their endpoints
+const CHANNELS_URL: &str = "https://github.com/mcp-scooter/scooter/releases/download/updater/channels.json";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UpdateChannel {
+    pub name: String,
+    /// Updater manifest URL of the channel
+    pub endpoint: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ChannelsManifest {
+    channels: Vec<UpdateChannel>,
+}
+
+/// Channels known to every build, used when `channels.json` can't be fetched
+fn builtin_channels() -> Vec<UpdateChannel> {
+    vec![
+        UpdateChannel { name: "stable".to_string(), endpoint: update_endpoint(false).to_string(), description: None },
+        UpdateChannel { name: "beta".to_string(), endpoint: update_endpoint(true).to_string(), description: None },
+    ]
+}
+
+static CHANNELS_CACHE: Mutex<Option<(std::time::Instant, Vec<UpdateChannel>)>> = Mutex::new(None);
+
+/// Fetch the channel list, reusing it for `UPDATE_CHECK_TTL`
+///
+/// Falls back to the built-in stable/beta channels if the manifest is
+/// unreachable or invalid.
+async fn fetch_update_channels() -> Vec<UpdateChannel> {
+    if let Ok(cache) = CHANNELS_CACHE.lock() {
+        if let Some((fetched_at, channels)) = cache.as_ref() {
+            if fetched_at.elapsed() < UPDATE_CHECK_TTL {
+                return channels.clone();
+            }
+        }
+    }
+
+    let fetched = async {
+        let resp = http_client()?
+            .get(CHANNELS_URL)
+            .timeout(Duration::from_secs(10))
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !resp.status().is_success() {
+            return Err(format!("HTTP {}", resp.status()));
+        }
+        let manifest: ChannelsManifest = resp.json().await.map_err(|e| e.to_string())?;
+        if manifest.channels.is_empty() {
+            return Err("no channels listed".to_string());
+        }
+        Ok(manifest.channels)
+    }.await;
+
+    match fetched {
+        Ok(channels) => {
+            if let Ok(mut cache) = CHANNELS_CACHE.lock() {
+                *cache = Some((std::time::Instant::now(), channels.clone()));
+            }
+            channels
+        }
+        Err(e) => {
+            eprintln!("Warning: Failed to fetch update channels, using built-in ones: {}", e);
+            builtin_channels()
+        }
+    }
+}
+
+/// List the update channels that can be picked in the `update_channel` setting
+#[tauri::command]
+async fn list_update_channels() -> Result<Vec<UpdateChannel>, String> {
+    Ok(fetch_update_channels().await)
+}
+
+/// Updater manifest URL to check
+///
+/// The `update_channel` setting, when set, names a channel from
+/// `channels.json` and overrides `include_beta`.
+async fn resolve_update_endpoint(app: &tauri::AppHandle, include_beta: bool) -> Result<String, String> {
+    let channel = app.try_state::<SettingsState>().and_then(|s| s.get().update_channel);
+    let Some(name) = channel.filter(|c| !c.is_empty()) else {
+        return Ok(update_endpoint(include_beta).to_string());
+    };
+    fetch_update_channels().await
+        .into_iter()
+        .find(|c| c.name == name)
+        .map(|c| c.endpoint)
+        .ok_or_else(|| format!("Unknown update channel '{}'", name))
+}
+
+/// Result of the last update check, reused for a short while
+struct CachedUpdateCheck {
+    endpoint: String,
+    pinned_version: Option<String>,
+    checked_at: std::time::Instant,
+    info: UpdateInfo,
+}
+
+static UPDATE_CHECK_CACHE: Mutex<Option<CachedUpdateCheck>> = Mutex::new(None);
+
+// How long a check result is reused before hitting the network again
+const UPDATE_CHECK_TTL: Duration = Duration::from_secs(300);
+
+/// The `pinned_version` setting without a leading `v`, if one is set
+fn pinned_version(app: &tauri::AppHandle) -> Option<String> {
+    app.try_state::<SettingsState>()?
+        .get()
+        .pinned_version
+        .map(|v| v.trim().trim_start_matches('v').to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Whether `SCOOTER_NO_UPDATE` is set to anything but "", "0" or "false"
+fn updates_disabled_by_env() -> bool {
+    std::env::var("SCOOTER_NO_UPDATE")
+        .map(|v| !matches!(v.trim().to_lowercase().as_str(), "" | "0" | "false"))
+        .unwrap_or(false)
+}
+
+/// Whether the updater is off by policy (`updates_disabled` or `SCOOTER_NO_UPDATE`)
+fn updates_disabled<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> bool {
+    updates_disabled_by_env()
+        || app.try_state::<SettingsState>().map(|s| s.get().updates_disabled).unwrap_or(false)
+}
+
+/// Fail fast when the updater is off by policy
+fn ensure_updates_enabled(app: &tauri::AppHandle) -> Result<(), String> {
+    if updates_disabled(app) {
+        return Err("updates are disabled by policy".to_string());
+    }
+    Ok(())
+}
+
+/// Whether the updater is off by policy, so the UI can hide its update controls
+#[tauri::command]
+async fn updates_policy_disabled(app: tauri::AppHandle) -> Result<bool, String> {
+    Ok(updates_disabled(&app))
+}
+
+/// Refuse an update that isn't the pinned version, when one is pinned
+fn check_pinned(app: &tauri::AppHandle, version: &str) -> Result<(), String> {
+    match pinned_version(app) {
+        Some(pinned) if pinned != version.trim_start_matches('v') => Err(format!(
+            "Update {} is not the pinned version {}",
+            version, pinned
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Create a custom updater for a channel's endpoint (see `resolve_update_endpoint`)
+///
+/// With a pinned version, an update is offered only when the manifest
+/// version equals the pin exactly (which may also be a downgrade; installing
+/// one needs `allow_downgrade`).
+fn build_updater(app: &tauri::AppHandle, endpoint: &str) -> Result<tauri_plugin_updater::Updater, String> {
+    let mut builder = app.updater_builder()
+        .endpoints(vec![endpoint.parse().map_err(|e: url::ParseError| format!("Invalid URL: {}", e))?])
+        .map_err(|e| format!("Failed to set endpoints: {}", e))?;
+    // The updater has its own HTTP client, so it needs the proxy passed on
+    let proxy = PROXY_CONFIG.lock().ok().and_then(|c| c.for_url(endpoint).map(str::to_string));
+    if let Some(proxy) = proxy {
+        match proxy.parse::<url::Url>() {
+            Ok(proxy) => builder = builder.proxy(proxy),
+            Err(e) => eprintln!("Warning: Ignoring invalid proxy '{}' for updates: {}", proxy, e),
+        }
+    }
+    if let Some(pinned) = pinned_version(app) {
+        builder = builder.version_comparator(move |current, remote| {
+            remote.version != current && remote.version.to_string() == pinned
+        });
+    }
+    builder.build()
+        .map_err(|e| format!("Failed to build updater: {}", e))
+}
+
+/// Size of an update's bundle, without downloading it
+///
+/// Uses a `size` field on the manifest's platform entry if the release
+/// publishes one, else the Content-Length of a HEAD request for the bundle.
+async fn update_download_size(update: &tauri_plugin_updater::Update) -> Option<u64> {
+    let from_manifest = update.raw_json.get("platforms")
+        .and_then(|p| p.get(&update.target))
+        .and_then(|p| p.get("size"))
+        .and_then(|s| s.as_u64());
+    if from_manifest.is_some() {
+        return from_manifest;
+    }
+    let resp = http_client().ok()?
+        .head(update.download_url.clone())
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await
+        .ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
+    resp.content_length().filter(|len| *len > 0)
+}
+
+/// Key id of the minisign key that made an updater signature
+///
+/// The updater signature is a base64 encoded minisign `.sig` file; its second
+/// line holds the algorithm (2 bytes), the key id (8 bytes) and the signature.
+fn signing_key_id(signature: &str) -> Option<String> {
+    use base64::Engine;
+    let engine = base64::engine::general_purpose::STANDARD;
+    let sig_file = String::from_utf8(engine.decode(signature.trim()).ok()?).ok()?;
+    let sig_line = sig_file.lines().nth(1)?;
+    let raw = engine.decode(sig_line.trim()).ok()?;
+    let key_id: [u8; 8] = raw.get(2..10)?.try_into().ok()?;
+    Some(format!("{:016X}", u64::from_le_bytes(key_id)))
+}
+
+/// Which update step failed
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateStage {
+    Check,
+    Download,
+    Install,
+}
+
+/// Where the app is in the update lifecycle, as returned by `update_status`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum UpdateLifecycle {
+    UpToDate,
+    Available { version: String },
+    /// `percent` is unknown when the server sends no Content-Length
+    Downloading { percent: Option<u8> },
+    /// Downloaded (or installed and waiting for a relaunch)
+    Downloaded { version: String },
+    Failed { stage: UpdateStage, message: String },
+}
+
+// Shared update state; only the update commands change it, so it always
+// matches what they last did
+static UPDATE_STATE: Mutex<UpdateLifecycle> = Mutex::new(UpdateLifecycle::UpToDate);
+
+fn set_update_state(state: UpdateLifecycle) {
+    if let Ok(mut current) = UPDATE_STATE.lock() {
+        // Download progress updates don't count as transitions
+        if std::mem::discriminant(&*current) != std::mem::discriminant(&state) {
+            debug_event(EventVerbosity::Transitions, "update-state", || serde_json::json!(state));
+        }
+        *current = state;
+    }
+}
+
+/// One entry of the on-disk update history
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UpdateEvent {
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub stage: UpdateStage,
+    /// The version that was running
+    pub from_version: String,
+    /// The version offered, downloaded or installed, if there was one
+    pub to_version: Option<String>,
+    pub success: bool,
+    /// What happened, e.g. "Up to date" or the error
+    pub detail: String,
+}
+
+const UPDATE_HISTORY_FILE: &str = "update-history.jsonl";
+
+// Set during setup; in the app data dir (or the portable dir) since, unlike
+// caches, the history shouldn't be cleaned up
+static UPDATE_HISTORY_PATH: std::sync::OnceLock<PathBuf> = std::sync::OnceLock::new();
+
+// Serializes appends within the app; each entry is also written with a
+// single append-mode write, so lines from concurrent writers never interleave
+static UPDATE_HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+/// Append an update check, download or install to the history file
+fn record_update_event(stage: UpdateStage, to_version: Option<&str>, outcome: Result<&str, &str>) {
+    use std::io::Write;
+    let Some(path) = UPDATE_HISTORY_PATH.get() else {
+        return;
+    };
+    let (success, detail) = match outcome {
+        Ok(detail) => (true, detail),
+        Err(detail) => (false, detail),
+    };
+    let event = UpdateEvent {
+        timestamp: unix_now(),
+        stage,
+        from_version: APP_VERSION.to_string(),
+        to_version: to_version.map(str::to_string),
+        success,
+        detail: detail.to_string(),
+    };
+    let Ok(mut line) = serde_json::to_string(&event) else {
+        return;
+    };
+    line.push('\n');
+
+    let _guard = UPDATE_HISTORY_LOCK.lock();
+    let result = path.parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::OpenOptions::new().create(true).append(true).open(path))
+        .and_then(|mut file| file.write_all(line.as_bytes()));
+    if let Err(e) = result {
+        eprintln!("Warning: Failed to record update history in {:?}: {}", path, e);
+    }
+}
+
+/// Every recorded update check, download and install, oldest first
+#[tauri::command]
+async fn update_history() -> Result<Vec<UpdateEvent>, String> {
+    read_update_history()
+}
+
+fn read_update_history() -> Result<Vec<UpdateEvent>, String> {
+    let Some(path) = UPDATE_HISTORY_PATH.get() else {
+        return Ok(Vec::new());
+    };
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read update history: {}", e)),
+    };
+    // A line cut short by a crash mid-write is skipped rather than failing the lot
+    Ok(text.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+}
+
+// An install this recent when we start means we are the relaunch it triggered
+const RELAUNCH_WINDOW_SECS: u64 = 120;
+
+// Set during setup to the version we were updated from, if we were just relaunched by an update
+static UPDATED_FROM: std::sync::OnceLock<Option<String>> = std::sync::OnceLock::new();
+
+/// The version the last install replaced, if it installed this build moments ago
+fn detect_updated_from() -> Option<String> {
+    let history = read_update_history().ok()?;
+    let last = history.iter().rev().find(|e| e.stage == UpdateStage::Install)?;
+    let just_installed = last.success
+        && last.to_version.as_deref() == Some(APP_VERSION)
+        && last.from_version != APP_VERSION
+        && unix_now().saturating_sub(last.timestamp) <= RELAUNCH_WINDOW_SECS;
+    just_installed.then(|| last.from_version.clone())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct VersionInfo {
+    pub app_version: String,
+    /// None when the backend isn't answering
+    pub backend_version: Option<String>,
+    /// Whether this is a pre-release (e.g. "1.2.0-beta.3")
+    pub is_beta_build: bool,
+    /// Set on the first launch after an update, to the version it replaced
+    pub updated_from: Option<String>,
+}
+
+/// Versions of the running app and backend, e.g. to confirm an update took effect
+#[tauri::command]
+async fn version_info(app: tauri::AppHandle) -> Result<VersionInfo, String> {
+    let app_version = app.package_info().version.clone();
+    let backend_version = match http_client() {
+        Ok(client) => fetch_backend_version(&client).await,
+        Err(_) => None,
+    }
+    .or_else(|| BACKEND_INFO.lock().ok().and_then(|info| info.clone()))
+    .map(|v| v.version);
+    Ok(VersionInfo {
+        is_beta_build: !app_version.pre.is_empty(),
+        app_version: app_version.to_string(),
+        backend_version,
+        updated_from: UPDATED_FROM.get().cloned().flatten(),
+    })
+}
+
+/// Record a failed update step and pass the error on
+fn update_failed<T>(stage: UpdateStage, result: Result<T, String>) -> Result<T, String> {
+    if let Err(message) = &result {
+        record_update_event(stage, None, Err(message));
+        let kind = match stage {
+            UpdateStage::Check => LifecycleEventKind::UpdateCheck,
+            UpdateStage::Download | UpdateStage::Install => LifecycleEventKind::UpdateInstall,
+        };
+        log_lifecycle(kind, format!("Failed: {}", message));
+        set_update_state(UpdateLifecycle::Failed { stage, message: message.clone() });
+    }
+    result
+}
+
+fn download_percent(downloaded: u64, total: Option<u64>) -> Option<u8> {
+    total.filter(|t| *t > 0).map(|t| (downloaded.saturating_mul(100) / t).min(100) as u8)
+}
+
+/// The update lifecycle state, the single source of truth for the dashboard
+#[tauri::command]
+async fn update_status() -> Result<UpdateLifecycle, String> {
+    UPDATE_STATE.lock().map(|s| s.clone()).map_err(|_| "Update state lock poisoned".to_string())
+}
+
+// Held for the duration of every updater operation, so a check (e.g. from
+// the auto-check timer) never builds a second updater mid-download or install
+static UPDATER_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Update info while another updater operation runs, from the last check
+/// or else the shared update state
+fn update_info_while_busy() -> UpdateInfo {
+    if let Some(info) = UPDATE_CHECK_CACHE.lock().ok().and_then(|c| c.as_ref().map(|c| c.info.clone())) {
+        return info;
+    }
+    let version = match UPDATE_STATE.lock().map(|s| s.clone()) {
+        Ok(UpdateLifecycle::Available { version } | UpdateLifecycle::Downloaded { version }) => Some(version),
+        _ => None,
+    };
+    UpdateInfo {
+        available: version.is_some(),
+        release_url: version.as_deref().map(release_page_url),
+        version,
+        version_change: None,
+        notes: None,
+        date: None,
+        download_size: None,
+        signing_key_id: None,
+    }
+}
+
+/// Take the updater lock for a check, or return what's already known
+/// (without waiting) if another updater operation holds it
+fn begin_update_check() -> Result<tokio::sync::MutexGuard<'static, ()>, UpdateInfo> {
+    UPDATER_LOCK.try_lock().map_err(|_| update_info_while_busy())
+}
+
+/// Check for updates using the appropriate channel (stable or beta, or the
+/// `update_channel` setting)
+///
+/// While a download or install is running this doesn't touch the network and
+/// returns what's already known instead.
+#[tauri::command]
+async fn check_for_updates(app: tauri::AppHandle, include_beta: bool) -> Result<UpdateInfo, String> {
+    ensure_updates_enabled(&app)?;
+    let _updater = match begin_update_check() {
+        Ok(guard) => guard,
+        Err(busy) => return Ok(busy),
+    };
+    let info = update_failed(UpdateStage::Check, fetch_update_info(&app, include_beta).await)?;
+    let outcome = match &info.version {
+        Some(version) if info.available => format!("Update {} available", version),
+        _ => "Up to date".to_string(),
+    };
+    record_update_event(UpdateStage::Check, info.version.as_deref(), Ok(&outcome));
+    log_lifecycle(LifecycleEventKind::UpdateCheck, outcome);
+    if let Ok(mut state) = UPDATE_STATE.lock() {
+        // A check doesn't undo a download that's in progress or done
+        if !matches!(*state, UpdateLifecycle::Downloading { .. } | UpdateLifecycle::Downloaded { .. }) {
+            *state = match &info.version {
+                Some(version) if info.available => UpdateLifecycle::Available { version: version.clone() },
+                _ => UpdateLifecycle::UpToDate,
+            };
+        }
+    }
+    Ok(info)
+}
+
+/// Fetch update info, reusing a recent check for the same endpoint and pin
+async fn fetch_update_info(app: &tauri::AppHandle, include_beta: bool) -> Result<UpdateInfo, String> {
+    let endpoint = resolve_update_endpoint(app, include_beta).await?;
+    let pinned = pinned_version(app);
+    if let Ok(cache) = UPDATE_CHECK_CACHE.lock() {
+        if let Some(cached) = cache.as_ref() {
+            if cached.endpoint == endpoint
+                && cached.pinned_version == pinned
+                && cached.checked_at.elapsed() < UPDATE_CHECK_TTL
+            {
+                return Ok(cached.info.clone());
+            }
+        }
+    }
+
+    let updater = build_updater(app, &endpoint)?;
+    
+    let info = match updater.check().await {
+        Ok(Some(update)) => {
+            UpdateInfo {
+                available: true,
+                version: Some(update.version.clone()),
+                version_change: Some(version_change(&update.current_version, &update.version)?),
+                notes: update.body.clone(),
+                date: update.date.map(|d: time::OffsetDateTime| d.to_string()),
+                release_url: Some(release_page_url(&update.version)),
+                download_size: update_download_size(&update).await,
+                signing_key_id: signing_key_id(&update.signature),
+            }
+        }
+        Ok(None) => {
+            UpdateInfo {
+                available: false,
+                version: None,
+                version_change: Some(VersionChange::UpToDate),
+                notes: None,
                 date: None,
-            })
+                release_url: None,
+                download_size: None,
+                signing_key_id: None,
+            }
+        }
+        Err(e) => {
+            return Err(format!("Failed to check for updates: {}", e));
+        }
+    };
+
+    if let Ok(mut cache) = UPDATE_CHECK_CACHE.lock() {
+        *cache = Some(CachedUpdateCheck {
+            endpoint,
+            pinned_version: pinned,
+            checked_at: std::time::Instant::now(),
+            info: info.clone(),
+        });
+    }
+    Ok(info)
+}
+
+/// Forget everything the updater knows: the cached check and channel list,
+/// the update state and any staged bundle, so the next check hits the network
+/// and an update has to be downloaded again
+///
+/// Refused while an update is being installed. The updater keeps downloaded
+/// bundles in memory until install, so there are no partial artifacts on disk
+/// to clean up.
+#[tauri::command]
+async fn reset_updater_state() -> Result<(), String> {
+    if LIFECYCLE.lock().map(|s| *s == LifecycleState::InstallingUpdate).unwrap_or(false) {
+        return Err("An update is being installed; try again once it has finished".to_string());
+    }
+    if let Ok(mut cache) = UPDATE_CHECK_CACHE.lock() {
+        *cache = None;
+    }
+    if let Ok(mut cache) = CHANNELS_CACHE.lock() {
+        *cache = None;
+    }
+    if let Ok(mut staged) = STAGED_UPDATE.lock() {
+        staged.take();
+    }
+    set_update_state(UpdateLifecycle::UpToDate);
+    Ok(())
+}
+
+/// What happens after an update has been installed
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InstallOutcome {
+    /// The new version is in place but only runs after `restart_app`
+    pub relaunch_required: bool,
+    /// The installer has taken over and the app is about to exit by itself
+    pub exiting: bool,
+}
+
+/// Platform behavior of the updater after `download_and_install` returns
+///
+/// On Windows the updater launches the NSIS/MSI installer, which closes the
+/// running app. On macOS and Linux the bundle/AppImage is replaced in place and
+/// the running process keeps using the old version until relaunched.
+fn install_outcome() -> InstallOutcome {
+    if cfg!(target_os = "windows") {
+        InstallOutcome { relaunch_required: false, exiting: true }
+    } else {
+        InstallOutcome { relaunch_required: true, exiting: false }
+    }
+}
+
+/// Relaunch the app, stopping the backend first (e.g. after an update or
+/// after changing a setting that needs a restart)
+#[tauri::command]
+async fn restart_app(app: tauri::AppHandle) -> Result<(), String> {
+    // Settings are saved on every change, but make sure nothing is lost
+    if let Some(settings) = app.try_state::<SettingsState>() {
+        settings.flush()?;
+    }
+    // restart() doesn't go through RunEvent::Exit, so clean up here
+    kill_all_backends();
+    app.restart();
+}
+
+/// An update that has been downloaded (and signature-checked) but not installed
+struct StagedUpdate {
+    update: tauri_plugin_updater::Update,
+    bytes: Vec<u8>,
+}
+
+// The staged bundle stays in memory rather than on disk: the updater verifies
+// the signature while downloading, and re-reading a file before install would
+// reopen a window for it to be swapped out
+static STAGED_UPDATE: Mutex<Option<StagedUpdate>> = Mutex::new(None);
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DownloadProgress {
+    pub downloaded: u64,
+    pub total: Option<u64>,
+}
+
+/// Download the available update without installing it
+///
+/// Emits `update-download-progress` while downloading; returns the staged
+/// version. Install it later with `apply_staged_update`. An older version
+/// (see `VersionChange::Downgrade`) is refused unless `allow_downgrade`.
+#[tauri::command]
+async fn download_update(app: tauri::AppHandle, include_beta: bool, allow_downgrade: Option<bool>) -> Result<String, String> {
+    ensure_updates_enabled(&app)?;
+    let _updater = UPDATER_LOCK.lock().await;
+    let version = update_failed(UpdateStage::Download, stage_update(&app, include_beta, allow_downgrade.unwrap_or(false)).await)?;
+    set_update_state(UpdateLifecycle::Downloaded { version: version.clone() });
+    record_update_event(UpdateStage::Download, Some(&version), Ok("Downloaded"));
+    Ok(version)
+}
+
+async fn stage_update(app: &tauri::AppHandle, include_beta: bool, allow_downgrade: bool) -> Result<String, String> {
+    let updater = build_updater(app, &resolve_update_endpoint(app, include_beta).await?)?;
+    let update = updater.check().await
+        .map_err(|e| format!("Failed to check for updates: {}", e))?
+        .ok_or("No update available")?;
+    check_pinned(app, &update.version)?;
+    check_downgrade(&update, allow_downgrade)?;
+
+    set_update_state(UpdateLifecycle::Downloading { percent: None });
+    let progress_handle = app.clone();
+    let mut downloaded: u64 = 0;
+    let bytes = update.download(
+        move |chunk_length: usize, content_length: Option<u64>| {
+            downloaded += chunk_length as u64;
+            set_update_state(UpdateLifecycle::Downloading { percent: download_percent(downloaded, content_length) });
+            let _ = progress_handle.emit("update-download-progress", DownloadProgress {
+                downloaded,
+                total: content_length,
+            });
+        },
+        || {}
+    )
+        .await
+        .map_err(|e| format!("Failed to download update: {}", e))?;
+
+    let version = update.version.clone();
+    if let Ok(mut staged) = STAGED_UPDATE.lock() {
+        *staged = Some(StagedUpdate { update, bytes });
+    }
+    Ok(version)
+}
+
+/// Install the update previously staged by `download_update`
+#[tauri::command]
+async fn apply_staged_update(app: tauri::AppHandle) -> Result<InstallOutcome, String> {
+    ensure_updates_enabled(&app)?;
+    let _lifecycle = begin_lifecycle(LifecycleState::InstallingUpdate)?;
+    let _updater = UPDATER_LOCK.lock().await;
+
+    let staged = STAGED_UPDATE.lock()
+        .map_err(|_| "Staged update lock poisoned".to_string())?
+        .take()
+        .ok_or("No update has been downloaded")?;
+    // The pin may have changed since the download; drop a stale download
+    update_failed(UpdateStage::Install, check_pinned(&app, &staged.update.version))?;
+
+    if let Err(e) = staged.update.install(&staged.bytes) {
+        let message = format!("Failed to install update: {}", e);
+        // Keep it staged so the install can be retried
+        if let Ok(mut slot) = STAGED_UPDATE.lock() {
+            *slot = Some(staged);
+        }
+        return update_failed(UpdateStage::Install, Err(message));
+    }
+    record_update_event(UpdateStage::Install, Some(&staged.update.version), Ok("Installed"));
+    log_lifecycle(LifecycleEventKind::UpdateInstall, format!("Installed {}", staged.update.version));
+    Ok(install_outcome())
+}
+
+/// Download and install the available update
+///
+/// An older version (see `VersionChange::Downgrade`) is refused unless
+/// `allow_downgrade`.
+#[tauri::command]
+async fn install_update(app: tauri::AppHandle, include_beta: bool, allow_downgrade: Option<bool>) -> Result<InstallOutcome, String> {
+    ensure_updates_enabled(&app)?;
+    // Hold the lifecycle for the whole check + install so a gateway restart
+    // can't race with (and orphan) the download
+    let _lifecycle = begin_lifecycle(LifecycleState::InstallingUpdate)?;
+    let _updater = UPDATER_LOCK.lock().await;
+
+    let updater = update_failed(
+        UpdateStage::Check,
+        async { build_updater(&app, &resolve_update_endpoint(&app, include_beta).await?) }.await,
+    )?;
+    
+    match updater.check().await {
+        Ok(Some(update)) => {
+            update_failed(UpdateStage::Install, check_pinned(&app, &update.version))?;
+            update_failed(UpdateStage::Install, check_downgrade(&update, allow_downgrade.unwrap_or(false)))?;
+            // Download and install
+            set_update_state(UpdateLifecycle::Downloading { percent: None });
+            let mut downloaded: u64 = 0;
+            let installed = update.download_and_install(
+                move |chunk_length: usize, content_length: Option<u64>| {
+                    downloaded += chunk_length as u64;
+                    set_update_state(UpdateLifecycle::Downloading { percent: download_percent(downloaded, content_length) });
+                },
+                || {}
+            )
+                .await
+                .map_err(|e| format!("Failed to install update: {}", e));
+            update_failed(UpdateStage::Install, installed)?;
+            set_update_state(UpdateLifecycle::Downloaded { version: update.version.clone() });
+            record_update_event(UpdateStage::Install, Some(&update.version), Ok("Installed"));
+            log_lifecycle(LifecycleEventKind::UpdateInstall, format!("Installed {}", update.version));
+            Ok(install_outcome())
+        }
+        Ok(None) => {
+            Err("No update available".to_string())
+        }
+        Err(e) => {
+            update_failed(UpdateStage::Check, Err(format!("Failed to check for updates: {}", e)))
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ConnectivityResult {
+    pub endpoint: String,
+    pub reachable: bool,
+    pub status_code: Option<u16>,
+    pub latency_ms: u64,
+    pub tls_ok: bool,
+    pub error: Option<String>,
+}
+
+/// Returns true if any error in the chain looks like a TLS/certificate failure
+fn is_tls_error(err: &(dyn std::error::Error + 'static)) -> bool {
+    let mut current: Option<&(dyn std::error::Error + 'static)> = Some(err);
+    while let Some(e) = current {
+        let msg = e.to_string().to_lowercase();
+        if msg.contains("certificate") || msg.contains("tls") || msg.contains("ssl") {
+            return true;
+        }
+        current = e.source();
+    }
+    false
+}
+
+/// Probe the update endpoint without checking for or installing an update
+///
+/// Useful to tell whether updates are blocked by a proxy or network policy.
+#[tauri::command]
+async fn test_update_connectivity(app: tauri::AppHandle, include_beta: bool) -> Result<ConnectivityResult, String> {
+    let endpoint = resolve_update_endpoint(&app, include_beta).await?;
+    let client = http_client()?;
+
+    let started = std::time::Instant::now();
+    let result = client.head(endpoint.as_str()).timeout(Duration::from_secs(10)).send().await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    Ok(match result {
+        Ok(resp) => ConnectivityResult {
+            endpoint,
+            reachable: true,
+            status_code: Some(resp.status().as_u16()),
+            latency_ms,
+            tls_ok: true,
+            error: None,
+        },
+        Err(e) => ConnectivityResult {
+            endpoint,
+            reachable: false,
+            status_code: e.status().map(|s| s.as_u16()),
+            latency_ms,
+            tls_ok: !is_tls_error(&e),
+            error: Some(e.to_string()),
+        },
+    })
+}
+
+/// Check that the backend's shutdown endpoint is there, without shutting down
+///
+/// Sends an OPTIONS request, which the backend never acts on. Any answer but
+/// 404 means the route exists (a 405 just means only POST is allowed). When
+/// this is false, restarting or quitting has to kill the backend instead.
+#[tauri::command]
+async fn test_shutdown_endpoint() -> Result<bool, String> {
+    let client = http_client()?;
+    let request = client.request(reqwest::Method::OPTIONS, endpoint_url(|e| e.shutdown.as_str()));
+    match request.timeout(Duration::from_secs(5)).send().await {
+        Ok(resp) => Ok(resp.status() != reqwest::StatusCode::NOT_FOUND),
+        Err(_) => Ok(false),
+    }
+}
+
+// Skew beyond which certificate and update checks may start failing
+const CLOCK_SKEW_WARN_SECS: u64 = 300;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ClockStatus {
+    /// Seconds since the Unix epoch, by our clock and by the server's
+    pub system_time: u64,
+    pub server_time: u64,
+    /// Positive when our clock is ahead
+    pub skew_secs: i64,
+    /// The URL whose `Date` header was compared against
+    pub source: String,
+    pub warning: Option<String>,
+}
+
+/// Seconds since the Unix epoch of an HTTP date like "Sun, 06 Nov 1994 08:49:37 GMT"
+fn parse_http_date(date: &str) -> Option<u64> {
+    const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+    let parts: Vec<&str> = date.split_whitespace().collect();
+    let [_, day, month, year, hms, "GMT"] = parts[..] else {
+        return None;
+    };
+    let month = MONTHS.iter().position(|m| *m == month)? as u8 + 1;
+    let mut hms = hms.split(':').map(|part| part.parse::<u8>().ok());
+    let (hour, minute, second) = (hms.next()??, hms.next()??, hms.next()??);
+    let date = time::Date::from_calendar_date(year.parse().ok()?, month.try_into().ok()?, day.parse().ok()?).ok()?;
+    let timestamp = date.with_hms(hour, minute, second).ok()?.assume_utc().unix_timestamp();
+    u64::try_from(timestamp).ok()
+}
+
+/// Compare the system clock with the `Date` header of the update server
+///
+/// A clock that's far off makes TLS certificates look expired (or not yet
+/// valid), so updates fail with confusing errors until it's corrected.
+#[tauri::command]
+async fn check_clock_skew(app: tauri::AppHandle) -> Result<ClockStatus, String> {
+    let result = measure_clock_skew(&app, Duration::from_secs(5)).await;
+    if let Ok(mut cache) = CLOCK_CHECK_CACHE.lock() {
+        *cache = Some((std::time::Instant::now(), result.clone().ok()));
+    }
+    result
+}
+
+// Last clock check (None if it failed), reused by diagnostics for a while so
+// they don't go to the network each time
+static CLOCK_CHECK_CACHE: Mutex<Option<(std::time::Instant, Option<ClockStatus>)>> = Mutex::new(None);
+const CLOCK_CHECK_TTL: Duration = Duration::from_secs(3600);
+
+// Diagnostics are local first, so they give the update server little time
+const DIAGNOSTICS_CLOCK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Clock status for diagnostics: the cached one, or a quick fresh check
+async fn diagnostics_clock_status(app: &tauri::AppHandle) -> Option<ClockStatus> {
+    if let Ok(cache) = CLOCK_CHECK_CACHE.lock() {
+        if let Some((_, status)) = cache.as_ref().filter(|(at, _)| at.elapsed() < CLOCK_CHECK_TTL) {
+            return status.clone();
+        }
+    }
+    // Bounds resolving a custom channel's endpoint too, not just the HEAD request
+    let status = tokio::time::timeout(DIAGNOSTICS_CLOCK_TIMEOUT, measure_clock_skew(app, DIAGNOSTICS_CLOCK_TIMEOUT))
+        .await
+        .ok()
+        .and_then(Result::ok);
+    if let Ok(mut cache) = CLOCK_CHECK_CACHE.lock() {
+        *cache = Some((std::time::Instant::now(), status.clone()));
+    }
+    status
+}
+
+async fn measure_clock_skew(app: &tauri::AppHandle, timeout: Duration) -> Result<ClockStatus, String> {
+    let source = resolve_update_endpoint(app, false).await?;
+    let resp = http_client()?
+        .head(&source)
+        .timeout(timeout)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach the update server (a wrong system clock can cause this): {}", e))?;
+    let system_time = unix_now();
+    let server_time = resp.headers()
+        .get(reqwest::header::DATE)
+        .and_then(|d| d.to_str().ok())
+        .and_then(parse_http_date)
+        .ok_or("The update server sent no usable Date header")?;
+
+    let skew_secs = system_time as i64 - server_time as i64;
+    let warning = (skew_secs.unsigned_abs() > CLOCK_SKEW_WARN_SECS).then(|| format!(
+        "The system clock is {}s {}; updates and secure connections may fail until it's corrected",
+        skew_secs.unsigned_abs(),
+        if skew_secs > 0 { "fast" } else { "slow" },
+    ));
+    Ok(ClockStatus { system_time, server_time, skew_secs, source, warning })
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Diagnostics {
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub backend_pid: Option<u32>,
+    pub gateway_running: Option<bool>,
+    pub control_port: Option<u16>,
+    pub mcp_port: Option<u16>,
+    pub active_profile_id: Option<String>,
+    pub profile_count: usize,
+    pub tool_count: usize,
+    /// Why the current settings are invalid (e.g. colliding ports), if they are
+    pub config_error: Option<String>,
+    /// Why the HTTP client couldn't be created; nothing reaches the backend then
+    pub network_error: Option<String>,
+    /// Whether the graceful shutdown endpoint answers (see `test_shutdown_endpoint`)
+    pub shutdown_endpoint_ok: Option<bool>,
+    /// How far the system clock is ahead of the update server's, if it answered
+    pub clock_skew_secs: Option<i64>,
+    pub warnings: Vec<String>,
+}
+
+/// Pid of the managed backend, if it is still alive
+fn managed_backend_pid() -> Option<u32> {
+    instance_pid(PRIMARY_INSTANCE)
+}
+
+/// Pid of a managed backend instance, if it is still alive
+fn instance_pid(id: &str) -> Option<u32> {
+    let mut guard = BACKEND_INSTANCES.lock().ok()?;
+    let child = guard.get_mut(id)?.child.as_mut()?;
+    match child.try_wait() {
+        Ok(None) => Some(child.id()),
+        _ => None,
+    }
+}
+
+/// Pids of all managed backend instances that are still alive
+fn managed_backend_pids() -> Vec<u32> {
+    let ids: Vec<String> = BACKEND_INSTANCES.lock().map(|guard| guard.keys().cloned().collect()).unwrap_or_default();
+    ids.iter().filter_map(|id| instance_pid(id)).collect()
+}
+
+/// Payload of the `network-init-failed` event
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NetworkInitFailed {
+    pub message: String,
+}
+
+/// Collect a snapshot of app and backend state for bug reports
+#[tauri::command]
+async fn collect_diagnostics(app: tauri::AppHandle) -> Result<Diagnostics, String> {
+    let client = http_client();
+    let status = match &client {
+        Ok(client) => fetch_status(client).await,
+        Err(_) => None,
+    };
+    let shutdown_endpoint_ok = test_shutdown_endpoint().await.ok();
+    let mut warnings = Vec::new();
+    if shutdown_endpoint_ok == Some(false) {
+        warnings.push(
+            "The backend's shutdown endpoint isn't reachable; restarting or quitting will kill it instead".to_string(),
+        );
+    }
+    let clock = diagnostics_clock_status(&app).await;
+    warnings.extend(clock.as_ref().and_then(|c| c.warning.clone()));
+
+    Ok(Diagnostics {
+        app_version: app.package_info().version.to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        backend_pid: managed_backend_pid(),
+        gateway_running: status.as_ref().map(|s| s.gateway_running),
+        control_port: status.as_ref().map(|s| s.control_port),
+        mcp_port: status.as_ref().map(|s| s.mcp_port),
+        active_profile_id: status.as_ref().map(|s| s.active_profile_id.clone()),
+        profile_count: status.as_ref().map(|s| s.profiles.len()).unwrap_or(0),
+        tool_count: status.as_ref()
+            .map(|s| s.profiles.iter().map(|p| p.tool_status.as_deref().unwrap_or_default().len()).sum())
+            .unwrap_or(0),
+        config_error: app.try_state::<SettingsState>().and_then(|s| s.get().validate().err()),
+        network_error: client.err(),
+        shutdown_endpoint_ok,
+        clock_skew_secs: clock.map(|c| c.skew_secs),
+        warnings,
+    })
+}
+
+const NEW_ISSUE_URL: &str = "https://github.com/mcp-scooter/scooter/issues/new";
+
+// Keep the pre-filled body well below the ~8 KB URL limits of browsers/GitHub,
+// leaving room for percent-encoding expansion
+const MAX_ISSUE_DIAGNOSTICS_CHARS: usize = 3000;
+
+/// Truncate a string to at most `max` characters, on a char boundary
+fn truncate_chars(s: &str, max: usize) -> String {
+    match s.char_indices().nth(max) {
+        Some((idx, _)) => format!("{}…", &s[..idx]),
+        None => s.to_string(),
+    }
+}
+
+/// Open a new GitHub issue pre-filled with diagnostics
+#[tauri::command]
+async fn report_bug(app: tauri::AppHandle) -> Result<(), String> {
+    let diagnostics = collect_diagnostics(app.clone()).await?;
+    let diagnostics_json = serde_json::to_string_pretty(&diagnostics).map_err(|e| e.to_string())?;
+
+    let body = format!(
+        "## Description\n\n<!-- What happened, and what did you expect to happen? -->\n\n\
+         ## Diagnostics\n\n```json\n{}\n```\n\n\
+         _Please attach the backend logs to this issue separately; they are not included here._\n",
+        truncate_chars(&diagnostics_json, MAX_ISSUE_DIAGNOSTICS_CHARS)
+    );
+
+    let url = url::Url::parse_with_params(NEW_ISSUE_URL, &[("body", body.as_str())])
+        .map_err(|e| format!("Invalid issue URL: {}", e))?;
+    app.opener()
+        .open_url(url.as_str(), None::<&str>)
+        .map_err(|e| format!("Failed to open issue page: {}", e))
+}
+
+/// Open the GitHub release page for a version in the default browser
+#[tauri::command]
+async fn open_release_notes(app: tauri::AppHandle, version: String) -> Result<(), String> {
+    app.opener()
+        .open_url(release_page_url(&version), None::<&str>)
+        .map_err(|e| format!("Failed to open release page: {}", e))
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DataDirStatus {
+    pub path: String,
+    pub writable: bool,
+    pub error: Option<String>,
+    pub fallback_path: Option<String>,
+    pub using_fallback: bool,
+}
+
+// Per-user data dir used instead of the default when the latter is read-only
+static DATA_DIR_OVERRIDE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+// Result of the last data dir writability probe
+static DATA_DIR_STATUS: Mutex<Option<DataDirStatus>> = Mutex::new(None);
+
+/// The OS user config dir, matching Go's `os.UserConfigDir()`
+#[cfg(target_os = "windows")]
+fn user_config_dir() -> Option<PathBuf> {
+    std::env::var_os("APPDATA").map(PathBuf::from)
+}
+
+#[cfg(target_os = "macos")]
+fn user_config_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|h| PathBuf::from(h).join("Library/Application Support"))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn user_config_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))
+}
+
+/// Directory the backend keeps its config, logs and registry in
+///
+/// Mirrors the resolution in cmd/scooter/main.go: `SCOOTER_CONFIG_DIR`, else
+/// `<user config dir>/mcp-scooter`. In portable mode the backend is always
+/// pointed at the portable data dir instead.
+fn backend_data_dir() -> Option<PathBuf> {
+    if let Some(dir) = configured_backend_data_dir() {
+        return Some(dir);
+    }
+    if let Some(dir) = std::env::var_os("SCOOTER_CONFIG_DIR").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(dir));
+    }
+    user_config_dir().map(|d| d.join("mcp-scooter"))
+}
+
+/// The data dir we pass to the backend, if any: the portable data dir, else
+/// the `data_dir` setting
+fn configured_backend_data_dir() -> Option<PathBuf> {
+    portable::backend_dir().or_else(|| DATA_DIR_OVERRIDE.lock().ok().and_then(|d| d.clone()))
+}
+
+/// Check that a directory exists (or can be created) and is writable
+fn probe_writable(dir: &std::path::Path) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Cannot create {:?}: {}", dir, e))?;
+    let probe = dir.join(format!(".scooter-write-test-{}", std::process::id()));
+    std::fs::write(&probe, b"ok").map_err(|e| format!("Cannot write to {:?}: {}", dir, e))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+/// Probe the backend data dir and remember the outcome for `data_dir_status`
+fn check_data_dir<R: tauri::Runtime>(handle: &tauri::AppHandle<R>) -> DataDirStatus {
+    let using_fallback = DATA_DIR_OVERRIDE.lock().map(|d| d.is_some()).unwrap_or(false);
+    // Portable mode has no per-user fallback to offer
+    let fallback = match portable::dir() {
+        Some(_) => None,
+        None => handle.path().app_local_data_dir().ok().map(|d| d.join("backend")),
+    };
+    let status = match backend_data_dir() {
+        Some(dir) => {
+            let result = probe_writable(&dir);
+            DataDirStatus {
+                path: dir.to_string_lossy().to_string(),
+                writable: result.is_ok(),
+                error: result.err(),
+                fallback_path: fallback.map(|d| d.to_string_lossy().to_string()),
+                using_fallback,
+            }
+        }
+        None => DataDirStatus {
+            path: String::new(),
+            writable: false,
+            error: Some("Could not determine the user config directory".to_string()),
+            fallback_path: fallback.map(|d| d.to_string_lossy().to_string()),
+            using_fallback,
+        },
+    };
+    if let Ok(mut last) = DATA_DIR_STATUS.lock() {
+        *last = Some(status.clone());
+    }
+    status
+}
+
+/// One directory checked by `repair_data_dir_permissions`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DataDirCheck {
+    pub path: String,
+    /// Created because it was missing
+    pub created: bool,
+    /// Permissions were changed to make it writable
+    pub repaired: bool,
+    pub writable: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DataDirRepair {
+    pub path: String,
+    pub checks: Vec<DataDirCheck>,
+    pub writable: bool,
+    /// Fixing the remaining problems needs an administrator (e.g. the dirs
+    /// belong to another account)
+    pub needs_elevation: bool,
+    pub message: Option<String>,
+}
+
+// Subdirectories the backend creates on startup (see cmd/scooter/main.go)
+const BACKEND_SUBDIRS: [&str; 5] = ["logs", "wasm", "registry/official", "registry/custom", "clients"];
+
+fn write_probe(dir: &std::path::Path) -> std::io::Result<()> {
+    let probe = dir.join(format!(".scooter-write-test-{}", std::process::id()));
+    std::fs::write(&probe, b"ok")?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+/// Try to give the current user write access to a directory
+#[cfg(unix)]
+fn grant_write_access(dir: &std::path::Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut perms = std::fs::metadata(dir).map_err(|e| e.to_string())?.permissions();
+    perms.set_mode(perms.mode() | 0o700);
+    std::fs::set_permissions(dir, perms).map_err(|e| e.to_string())
+}
+
+/// Try to give the current user write access to a directory
+///
+/// Clears the read-only attribute, then grants the user full control with
+/// icacls, which only works without elevation if we may change the ACL.
+#[cfg(windows)]
+fn grant_write_access(dir: &std::path::Path) -> Result<(), String> {
+    let mut perms = std::fs::metadata(dir).map_err(|e| e.to_string())?.permissions();
+    if perms.readonly() {
+        perms.set_readonly(false);
+        let _ = std::fs::set_permissions(dir, perms);
+    }
+    let user = std::env::var("USERNAME").map_err(|_| "USERNAME is not set".to_string())?;
+    let output = background_command("icacls")
+        .arg(dir)
+        .args(["/grant", &format!("{}:(OI)(CI)F", user)])
+        .output()
+        .map_err(|e| format!("Failed to run icacls: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stdout).trim().to_string());
+    }
+    Ok(())
+}
+
+/// Check one directory, creating it or fixing its permissions as needed
+fn repair_dir(dir: &std::path::Path) -> DataDirCheck {
+    let mut check = DataDirCheck {
+        path: dir.to_string_lossy().to_string(),
+        created: false,
+        repaired: false,
+        writable: false,
+        error: None,
+    };
+    if !dir.is_dir() {
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            check.error = Some(match e.kind() {
+                std::io::ErrorKind::PermissionDenied => format!("Permission denied creating it: {}", e),
+                _ => format!("Cannot create: {}", e),
+            });
+            return check;
+        }
+        check.created = true;
+    }
+    match write_probe(dir) {
+        Ok(()) => check.writable = true,
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+            let fixed = grant_write_access(dir).and_then(|_| write_probe(dir).map_err(|e| e.to_string()));
+            match fixed {
+                Ok(()) => {
+                    check.repaired = true;
+                    check.writable = true;
+                }
+                Err(e) => check.error = Some(format!("Permission denied, and repair failed: {}", e)),
+            }
+        }
+        Err(e) => check.error = Some(format!("Cannot write: {}", e)),
+    }
+    check
+}
+
+/// Check the backend data dir and its subdirectories, creating missing ones
+/// and fixing permissions where the current user may
+fn repair_data_dir(dir: &std::path::Path) -> DataDirRepair {
+    let mut checks = vec![repair_dir(dir)];
+    if checks[0].writable {
+        checks.extend(BACKEND_SUBDIRS.iter().map(|sub| repair_dir(&dir.join(sub))));
+    }
+    let writable = checks.iter().all(|c| c.writable);
+    let needs_elevation = checks.iter().any(|c| c.error.as_deref().is_some_and(|e| e.starts_with("Permission denied")));
+    let message = if writable {
+        None
+    } else if needs_elevation {
+        Some(format!(
+            "{} belongs to another account. Fix its permissions as an administrator, or use the per-user data dir instead.",
+            dir.display()
+        ))
+    } else {
+        checks.iter().find_map(|c| c.error.clone())
+    };
+    DataDirRepair { path: dir.to_string_lossy().to_string(), checks, writable, needs_elevation, message }
+}
+
+/// Verify the backend data dir is writable, repairing what we can
+#[tauri::command]
+async fn repair_data_dir_permissions(app: tauri::AppHandle) -> Result<DataDirRepair, String> {
+    let dir = backend_data_dir().ok_or("Could not determine the backend data dir")?;
+    let report = tauri::async_runtime::spawn_blocking(move || repair_data_dir(&dir))
+        .await
+        .map_err(|e| format!("Repair failed: {}", e))?;
+    // Refresh what data_dir_status reports
+    check_data_dir(&app);
+    Ok(report)
+}
+
+/// Report whether the backend data directory is writable
+#[tauri::command]
+async fn data_dir_status(app: tauri::AppHandle) -> Result<DataDirStatus, String> {
+    match DATA_DIR_STATUS.lock().ok().and_then(|s| s.clone()) {
+        Some(status) => Ok(status),
+        None => Ok(check_data_dir(&app)),
+    }
+}
+
+/// Switch the backend to a per-user data dir and start it there
+///
+/// Used when the default data dir turned out to be read-only.
+#[tauri::command]
+async fn use_fallback_data_dir(app: tauri::AppHandle) -> Result<DataDirStatus, String> {
+    if portable::dir().is_some() {
+        return Err("Portable mode keeps backend data beside the executable; move the app to a writable location".to_string());
+    }
+    let fallback = app.path().app_local_data_dir()
+        .map_err(|e| format!("Failed to resolve app data dir: {}", e))?
+        .join("backend");
+    probe_writable(&fallback)?;
+    // Persist the choice so the next launch doesn't hit the read-only dir again
+    let settings = app.state::<SettingsState>()
+        .update(|s| s.data_dir = Some(fallback.to_string_lossy().to_string()))?;
+    apply_settings(&app, &settings);
+
+    let status = check_data_dir(&app);
+    if managed_backend_pid().is_none() {
+        store_backend(spawn_backend()?);
+        println!("Backend process started with fallback data dir");
+    }
+    Ok(status)
+}
+
+/// Disk usage of one top-level entry of the backend data dir
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DirEntryUsage {
+    pub name: String,
+    pub is_dir: bool,
+    pub bytes: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DirUsage {
+    pub path: String,
+    pub total_bytes: u64,
+    /// Largest first
+    pub entries: Vec<DirEntryUsage>,
+    /// The walk hit the depth or time limit, so the sizes are a lower bound
+    pub truncated: bool,
+}
+
+// Limits for walking the data dir, so a huge or deeply nested one can't hang
+const USAGE_MAX_DEPTH: usize = 16;
+const USAGE_TIME_BUDGET: Duration = Duration::from_secs(5);
+
+/// Total size of the files under `dir`, and whether the walk was cut short
+///
+/// Symlinks aren't followed, so links out of the data dir aren't counted.
+fn dir_size(dir: &std::path::Path, deadline: std::time::Instant) -> (u64, bool) {
+    let mut total = 0;
+    let mut truncated = false;
+    let mut pending = vec![(dir.to_path_buf(), 0)];
+    while let Some((dir, depth)) = pending.pop() {
+        if std::time::Instant::now() >= deadline {
+            return (total, true);
+        }
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(meta) = entry.path().symlink_metadata() else {
+                continue;
+            };
+            if meta.is_dir() {
+                if depth + 1 < USAGE_MAX_DEPTH {
+                    pending.push((entry.path(), depth + 1));
+                } else {
+                    truncated = true;
+                }
+            } else if meta.is_file() {
+                total += meta.len();
+            }
+        }
+    }
+    (total, truncated)
+}
+
+/// Size of the backend data dir, broken down by top-level entry
+#[tauri::command]
+async fn data_dir_usage() -> Result<DirUsage, String> {
+    let dir = backend_data_dir().ok_or("Could not determine the backend data dir")?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let deadline = std::time::Instant::now() + USAGE_TIME_BUDGET;
+        let mut truncated = false;
+        let mut entries = Vec::new();
+        let listing = std::fs::read_dir(&dir).map_err(|e| format!("Failed to read {:?}: {}", dir, e))?;
+        for entry in listing.flatten() {
+            let Ok(meta) = entry.path().symlink_metadata() else {
+                continue;
+            };
+            let bytes = if meta.is_dir() {
+                let (bytes, cut_short) = dir_size(&entry.path(), deadline);
+                truncated |= cut_short;
+                bytes
+            } else {
+                meta.len()
+            };
+            entries.push(DirEntryUsage {
+                name: entry.file_name().to_string_lossy().to_string(),
+                is_dir: meta.is_dir(),
+                bytes,
+            });
+        }
+        entries.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+        Ok(DirUsage {
+            path: dir.to_string_lossy().to_string(),
+            total_bytes: entries.iter().map(|e| e.bytes).sum(),
+            entries,
+            truncated,
+        })
+    })
+    .await
+    .map_err(|e| format!("Data dir walk failed: {}", e))?
+}
+
+/// The backend's daily log files, oldest first
+fn backend_log_files() -> Result<Vec<(PathBuf, std::fs::Metadata)>, String> {
+    let dir = backend_data_dir().ok_or("Could not determine the backend data dir")?.join("logs");
+    let mut logs: Vec<(PathBuf, std::fs::Metadata)> = std::fs::read_dir(&dir).into_iter().flatten().flatten()
+        .filter(|e| e.file_name().to_string_lossy().ends_with(".log"))
+        .filter_map(|e| e.metadata().ok().map(|m| (e.path(), m)))
+        .collect();
+    logs.sort_by_key(|(_, meta)| meta.modified().ok());
+    Ok(logs)
+}
+
+// Backend log lines the tray's Copy Recent Logs item copies
+const TRAY_COPIED_LOG_LINES: usize = 200;
+
+// Keep copied logs well within what clipboards and issue forms handle
+const MAX_COPIED_LOG_CHARS: usize = 64 * 1024;
+
+/// Copy the last `lines` lines of the backend log and the lifecycle log to
+/// the clipboard, for pasting into a bug report
+///
+/// If the result is too long, the oldest backend log lines are dropped.
+#[tauri::command]
+async fn copy_recent_logs(app: tauri::AppHandle, lines: usize) -> Result<(), String> {
+    let backend_log = match backend_log_files()?.pop() {
+        Some((path, _)) => {
+            let text = std::fs::read(&path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+            let text = String::from_utf8_lossy(&text).to_string();
+            let all: Vec<&str> = text.lines().collect();
+            all[all.len().saturating_sub(lines)..].join("\n")
+        }
+        None => "(no backend log file)".to_string(),
+    };
+    let lifecycle = LIFECYCLE_LOG.lock()
+        .map(|log| {
+            log.iter()
+                .map(|e| {
+                    let at = time::OffsetDateTime::from_unix_timestamp(e.timestamp as i64)
+                        .map(|t| t.to_string())
+                        .unwrap_or_else(|_| e.timestamp.to_string());
+                    format!("{} {:?} {}", at, e.kind, e.detail)
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default();
+
+    let lifecycle_section = format!("=== Lifecycle events ===\n{}\n", lifecycle);
+    let header = format!("=== MCP Scooter v{} logs ===\n\n", APP_VERSION);
+    let budget = MAX_COPIED_LOG_CHARS.saturating_sub(header.len() + lifecycle_section.len() + 32);
+    let backend_chars = backend_log.chars().count();
+    let backend_log = if backend_chars > budget {
+        // Keep the newest lines, which are at the end
+        let tail: String = backend_log.chars().skip(backend_chars - budget).collect();
+        format!("…{}", tail)
+    } else {
+        backend_log
+    };
+    let text = format!("{}{}\n=== Backend log ===\n{}\n", header, lifecycle_section, backend_log);
+    app.clipboard().write_text(text).map_err(|e| format!("Failed to copy logs: {}", e))
+}
+
+/// Clear the backend's logs, returning how many bytes of old log files were
+/// deleted
+///
+/// A running backend clears the log it's writing to itself (if it supports
+/// that); the older daily log files are deleted here. Only when the backend
+/// is known to be down does the newest log file go too.
+#[tauri::command]
+async fn clear_backend_logs() -> Result<u64, String> {
+    let resp = http_client()?.delete(format!("{}/api/logs", control_api())).send().await;
+    match &resp {
+        // An older backend without the endpoint keeps its current log
+        Ok(resp) if resp.status() == reqwest::StatusCode::NOT_FOUND => {}
+        Ok(resp) if !resp.status().is_success() => {
+            return Err(format!("Backend returned {} clearing its log", resp.status()));
+        }
+        _ => {}
+    }
+    // Not answering isn't enough: a hung or starting backend may still write to it
+    let backend_stopped = resp.is_err() && managed_backend_pid().is_none();
+
+    let mut logs = backend_log_files()?;
+    if !backend_stopped {
+        // The newest file is the one the backend has open
+        logs.pop();
+    }
+    let mut freed = 0;
+    for (path, meta) in logs {
+        if std::fs::remove_file(&path).is_ok() {
+            freed += meta.len();
+        }
+    }
+    Ok(freed)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ConfigInvalid {
+    pub message: String,
+}
+
+/// Push settings that the rest of the app reads from statics into place
+///
+/// A changed `data_dir` or `sandbox_command` takes effect the next time the
+/// backend is spawned.
+fn apply_settings<R: tauri::Runtime>(handle: &tauri::AppHandle<R>, settings: &Settings) {
+    CONTROL_PORT.store(settings.control_port, Ordering::Relaxed);
+    MCP_PORT.store(settings.mcp_port, Ordering::Relaxed);
+    POLL_INTERVAL_SECS.store(settings.poll_interval_secs, Ordering::Relaxed);
+    TRAY_DEBOUNCE_MS.store(settings.tray_debounce_ms, Ordering::Relaxed);
+    if let Ok(mut endpoints) = BACKEND_ENDPOINTS.lock() {
+        *endpoints = Some(settings.endpoints.clone());
+    }
+    TRAY_COMPACT_TITLE.store(settings.tray_compact_title, Ordering::Relaxed);
+    if let Ok(mut scope) = ICON_SCOPE.lock() {
+        *scope = settings.icon_scope;
+    }
+    if let Ok(mut dir) = DATA_DIR_OVERRIDE.lock() {
+        *dir = settings.data_dir.as_ref().filter(|d| !d.is_empty()).map(PathBuf::from);
+    }
+    if let Ok(mut sandbox) = SANDBOX_COMMAND.lock() {
+        *sandbox = settings.sandbox_command.clone();
+    }
+    if let Ok(mut inheritance) = ENV_INHERITANCE.lock() {
+        *inheritance = settings.env_inheritance;
+    }
+    // Switching elevation on again means asking again after a cancelled prompt
+    if settings.run_elevated && !RUN_ELEVATED.swap(true, Ordering::SeqCst) {
+        ELEVATION_DECLINED.store(false, Ordering::SeqCst);
+    }
+    RUN_ELEVATED.store(settings.run_elevated, Ordering::SeqCst);
+    let proxy = ProxyConfig::from_settings(settings);
+    let proxy_changed = PROXY_CONFIG.lock().map(|mut current| std::mem::replace(&mut *current, proxy.clone()) != proxy);
+    if proxy_changed.unwrap_or(false) {
+        // Rebuild the client (once something has built it) with the new proxies
+        let built = HTTP_CLIENT.lock().map(|mut client| client.take().is_some()).unwrap_or(false);
+        if built {
+            POLLER_RESET.notify_one();
+        }
+    }
+    MAX_BACKEND_MEMORY_MB.store(settings.max_backend_memory_mb.unwrap_or(0), Ordering::Relaxed);
+    MEMORY_LIMIT_CGROUP.store(settings.memory_limit_cgroup, Ordering::Relaxed);
+    if let Ok(mut binary) = BACKEND_BINARY.lock() {
+        *binary = settings.backend_binary.as_ref().filter(|b| !b.is_empty()).map(PathBuf::from);
+    }
+    if let Some(tray) = handle.tray_by_id("main-tray") {
+        let _ = tray.set_show_menu_on_left_click(settings.effective_left_click() == TrayLeftClick::Menu);
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PrivilegedPort {
+    pub setting: String,
+    pub port: u16,
+    pub guidance: String,
+}
+
+/// Whether this process may bind ports below 1024
+///
+/// Linux and the BSDs require root (or CAP_NET_BIND_SERVICE, which we can't
+/// cheaply check); macOS and Windows don't restrict them.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn can_bind_privileged_ports() -> bool {
+    // SAFETY: geteuid has no preconditions and cannot fail
+    unsafe { libc::geteuid() == 0 }
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn can_bind_privileged_ports() -> bool {
+    true
+}
+
+/// Warn, via `privileged-port`, about configured ports we won't be able to bind
+///
+/// Without this the only symptom would be a backend that never comes up.
+fn check_privileged_ports<R: tauri::Runtime>(handle: &tauri::AppHandle<R>, settings: &Settings) {
+    if can_bind_privileged_ports() {
+        return;
+    }
+    for (setting, port) in settings.privileged_ports() {
+        eprintln!("Warning: {} {} is privileged and the app isn't running as root", setting, port);
+        let _ = handle.emit("privileged-port", PrivilegedPort {
+            setting,
+            port,
+            guidance: format!(
+                "Port {} is below 1024, which needs root to bind on this system. \
+                 Choose a port of 1024 or above in both the app and gateway settings.",
+                port
+            ),
+        });
+    }
+}
+
+/// Current desktop app settings
+#[tauri::command]
+async fn get_settings(settings: tauri::State<'_, SettingsState>) -> Result<Settings, String> {
+    Ok(settings.get())
+}
+
+/// Change the given settings, leaving the others as they are
+///
+/// `partial` is a JSON object with a subset of the `Settings` fields. Ports
+/// below 1024 are only accepted with `allow_privileged`.
+#[tauri::command]
+async fn update_settings(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SettingsState>,
+    partial: serde_json::Value,
+    allow_privileged: Option<bool>,
+) -> Result<Settings, String> {
+    let before = settings.get();
+    let updated = settings.update_partial(partial, allow_privileged.unwrap_or(false))?;
+    apply_settings(&app, &updated);
+    if before.tray_tool_layout != updated.tray_tool_layout {
+        update_tray(&app, &last_status());
+    }
+    let needs_restart = settings::restart_required_changes(&before, &updated);
+    if !needs_restart.is_empty() {
+        let _ = app.emit("settings-needs-restart", SettingsNeedsRestart { settings: needs_restart });
+    }
+    Ok(updated)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SettingsSchemaVersion {
+    /// The version this build reads and writes
+    pub current: u32,
+    /// The settings file's version; older until the migrated settings are
+    /// first saved, newer if a later build wrote it
+    pub file: Option<u32>,
+}
+
+/// Schema versions of the settings, see `settings::migrate`
+#[tauri::command]
+async fn settings_schema_version(settings: tauri::State<'_, SettingsState>) -> Result<SettingsSchemaVersion, String> {
+    Ok(SettingsSchemaVersion { current: settings::SETTINGS_VERSION, file: settings.file_version() })
+}
+
+/// Payload of `settings-needs-restart`, so the UI can offer `restart_app`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SettingsNeedsRestart {
+    pub settings: Vec<String>,
+}
+
+/// Add a tool to (or remove it from) the `muted_tools` setting
+fn set_tool_muted<R: tauri::Runtime>(app: &tauri::AppHandle<R>, name: &str, muted: bool) -> Result<Settings, String> {
+    app.state::<SettingsState>().update(|s| {
+        s.muted_tools.retain(|t| t != name);
+        if muted {
+            s.muted_tools.push(name.to_string());
+        }
+    })
+}
+
+/// Stop notifying about status changes of a tool
+#[tauri::command]
+async fn mute_tool(app: tauri::AppHandle, name: String) -> Result<Settings, String> {
+    let settings = set_tool_muted(&app, &name, true)?;
+    update_tray(&app, &last_status());
+    Ok(settings)
+}
+
+/// Notify about status changes of a muted tool again
+#[tauri::command]
+async fn unmute_tool(app: tauri::AppHandle, name: String) -> Result<Settings, String> {
+    let settings = set_tool_muted(&app, &name, false)?;
+    update_tray(&app, &last_status());
+    Ok(settings)
+}
+
+/// Every setting in effect with where its value came from: `default`,
+/// `file` (the settings file), `env` or `portable`
+///
+/// Also lists values resolved outside the settings, like the backend data
+/// dir and the backend command.
+#[tauri::command]
+async fn effective_config(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    let state = app.state::<SettingsState>();
+    let settings = state.get();
+    let in_file = state.file_keys();
+    let entry = |value: serde_json::Value, source: &str| serde_json::json!({ "value": value, "source": source });
+
+    let serde_json::Value::Object(fields) = serde_json::to_value(&settings).map_err(|e| e.to_string())? else {
+        return Err("Settings must serialize to an object".to_string());
+    };
+    let mut config: serde_json::Map<String, serde_json::Value> = fields
+        .into_iter()
+        .map(|(key, value)| {
+            let source = if in_file.contains(&key) { "file" } else { "default" };
+            (key, entry(value, source))
+        })
+        .collect();
+
+    let health_port = agent_health_port(&settings);
+    if health_port != settings.agent_health_port {
+        config.insert("agent_health_port".to_string(), entry(serde_json::json!(health_port), "env"));
+    }
+
+    let data_dir_source = if portable::dir().is_some() {
+        "portable"
+    } else if settings.data_dir.as_deref().is_some_and(|d| !d.is_empty()) {
+        "file"
+    } else if std::env::var_os("SCOOTER_CONFIG_DIR").is_some_and(|d| !d.is_empty()) {
+        "env"
+    } else {
+        "default"
+    };
+    let data_dir = backend_data_dir().map(|d| d.to_string_lossy().to_string());
+    config.insert("backend_data_dir".to_string(), entry(serde_json::json!(data_dir), data_dir_source));
+
+    let backend_command = match backend_command_override() {
+        Ok(Some(args)) => entry(serde_json::json!(args), "env"),
+        _ => {
+            let path = backend_location().ok().map(|l| l.path.to_string_lossy().to_string());
+            let source = if settings.backend_binary.is_some() { "file" } else { "default" };
+            entry(serde_json::json!(path), source)
+        }
+    };
+    config.insert("backend_command".to_string(), backend_command);
+
+    if updates_disabled_by_env() {
+        config.insert("updates_disabled".to_string(), entry(serde_json::json!(true), "env"));
+    }
+
+    let proxy = ProxyConfig::from_settings(&settings);
+    for (key, setting, effective) in [
+        ("http_proxy", &settings.http_proxy, proxy.http),
+        ("https_proxy", &settings.https_proxy, proxy.https),
+        ("no_proxy", &settings.no_proxy, proxy.no_proxy),
+    ] {
+        if setting.as_deref().unwrap_or_default().trim().is_empty() && effective.is_some() {
+            config.insert(key.to_string(), entry(serde_json::json!(effective), "env"));
+        }
+    }
+
+    Ok(serde_json::Value::Object(config))
+}
+
+/// Id of a snapshot taken by `snapshot_state`
+pub type SnapshotId = u64;
+
+/// App state captured by `snapshot_state`
+struct StateSnapshot {
+    id: SnapshotId,
+    label: String,
+    taken_at: u64,
+    status: Option<AppStatus>,
+    config: serde_json::Value,
+    resources: Vec<ProcessResourceUsage>,
+}
+
+// How many snapshots are kept; the oldest is dropped first
+const MAX_SNAPSHOTS: usize = 20;
+
+static SNAPSHOTS: Mutex<std::collections::VecDeque<StateSnapshot>> = Mutex::new(std::collections::VecDeque::new());
+static NEXT_SNAPSHOT_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+/// Capture the status, effective config and resource usage under a label,
+/// for comparing with `compare_snapshots` after changing something
+#[tauri::command]
+async fn snapshot_state(app: tauri::AppHandle, label: String) -> Result<SnapshotId, String> {
+    let status = fetch_status(&http_client()?).await.or_else(last_status);
+    let config = effective_config(app).await?;
+    let resources = all_scooter_resources().await?;
+    let id = NEXT_SNAPSHOT_ID.fetch_add(1, Ordering::Relaxed);
+
+    let mut snapshots = SNAPSHOTS.lock().map_err(|_| "Snapshot lock poisoned".to_string())?;
+    if snapshots.len() == MAX_SNAPSHOTS {
+        snapshots.pop_front();
+    }
+    snapshots.push_back(StateSnapshot { id, label, taken_at: unix_now(), status, config, resources });
+    Ok(id)
+}
+
+/// A tool whose status differs between two snapshots; None means the tool
+/// wasn't listed
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ToolStatusChange {
+    pub profile: String,
+    pub tool: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ConfigChange {
+    pub key: String,
+    pub before: serde_json::Value,
+    pub after: serde_json::Value,
+}
+
+/// What changed from snapshot `a` to snapshot `b`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SnapshotDiff {
+    pub a: String,
+    pub b: String,
+    /// Seconds between the two snapshots
+    pub elapsed_secs: i64,
+    /// None when the backend was unreachable for that snapshot
+    pub gateway_running: (Option<bool>, Option<bool>),
+    pub active_profile: (Option<String>, Option<String>),
+    pub tools: Vec<ToolStatusChange>,
+    pub config: Vec<ConfigChange>,
+    pub backend_memory_bytes: (Option<u64>, Option<u64>),
+}
+
+/// Status of every listed tool by (profile, tool)
+fn tool_statuses(status: &Option<AppStatus>) -> std::collections::BTreeMap<(String, String), String> {
+    status.iter()
+        .flat_map(|s| &s.profiles)
+        .flat_map(|p| p.tool_status.as_deref().unwrap_or_default().iter().map(move |t| (p, t)))
+        .map(|(p, t)| ((p.id.clone(), t.name.clone()), t.status.clone()))
+        .collect()
+}
+
+/// Structured diff of two snapshots taken by `snapshot_state`
+#[tauri::command]
+async fn compare_snapshots(a: SnapshotId, b: SnapshotId) -> Result<SnapshotDiff, String> {
+    let snapshots = SNAPSHOTS.lock().map_err(|_| "Snapshot lock poisoned".to_string())?;
+    let find = |id: SnapshotId| {
+        snapshots.iter()
+            .find(|s| s.id == id)
+            .ok_or_else(|| format!("No snapshot {} (only the last {} are kept)", id, MAX_SNAPSHOTS))
+    };
+    let (a, b) = (find(a)?, find(b)?);
+
+    let (before, after) = (tool_statuses(&a.status), tool_statuses(&b.status));
+    let keys: std::collections::BTreeSet<&(String, String)> = before.keys().chain(after.keys()).collect();
+    let tools = keys.into_iter()
+        .filter(|key| before.get(*key) != after.get(*key))
+        .map(|key| ToolStatusChange {
+            profile: key.0.clone(),
+            tool: key.1.clone(),
+            before: before.get(key).cloned(),
+            after: after.get(key).cloned(),
+        })
+        .collect();
+
+    // Only values count; a value moving from default to the file is no change
+    let value = |config: &serde_json::Value, key: &str| config.get(key).and_then(|e| e.get("value")).cloned().unwrap_or_default();
+    let keys: std::collections::BTreeSet<&String> = a.config.as_object().into_iter()
+        .chain(b.config.as_object())
+        .flat_map(|c| c.keys())
+        .collect();
+    let config = keys.into_iter()
+        .filter_map(|key| {
+            let (before, after) = (value(&a.config, key), value(&b.config, key));
+            (before != after).then(|| ConfigChange { key: key.clone(), before, after })
+        })
+        .collect();
+
+    let memory = |s: &StateSnapshot| s.resources.iter().find(|r| r.managed).map(|r| r.memory_bytes);
+    Ok(SnapshotDiff {
+        a: a.label.clone(),
+        b: b.label.clone(),
+        elapsed_secs: b.taken_at as i64 - a.taken_at as i64,
+        gateway_running: (a.status.as_ref().map(|s| s.gateway_running), b.status.as_ref().map(|s| s.gateway_running)),
+        active_profile: (
+            a.status.as_ref().map(|s| s.active_profile_id.clone()),
+            b.status.as_ref().map(|s| s.active_profile_id.clone()),
+        ),
+        tools,
+        config,
+        backend_memory_bytes: (memory(a), memory(b)),
+    })
+}
+
+/// Pin the main window above other windows (or unpin it) and remember it
+#[tauri::command]
+async fn set_always_on_top(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window("main") {
+        window.set_always_on_top(enabled).map_err(|e| format!("Failed to set always on top: {}", e))?;
+    }
+    app.state::<SettingsState>().update(|s| s.always_on_top = enabled)?;
+    update_tray(&app, &last_status());
+    Ok(())
+}
+
+/// Set what closing the main window does
+#[tauri::command]
+async fn set_close_behavior(settings: tauri::State<'_, SettingsState>, behavior: CloseBehavior) -> Result<(), String> {
+    settings.update(|s| s.close_behavior = behavior).map(|_| ())
+}
+
+/// Quit or hide the main window as if the close prompt had been answered
+///
+/// With `remember`, the choice is saved so the prompt isn't shown again.
+#[tauri::command]
+async fn resolve_close_request(app: tauri::AppHandle, quit: bool, remember: bool) -> Result<(), String> {
+    apply_close_choice(&app, quit, remember)
+}
+
+fn apply_close_choice<R: tauri::Runtime>(handle: &tauri::AppHandle<R>, quit: bool, remember: bool) -> Result<(), String> {
+    if remember {
+        let behavior = if quit { CloseBehavior::Quit } else { CloseBehavior::Hide };
+        if let Some(settings) = handle.try_state::<SettingsState>() {
+            settings.update(|s| s.close_behavior = behavior)?;
+        }
+    }
+    if quit {
+        // Exit through the normal path so RunEvent::Exit stops the backend
+        handle.exit(0);
+    } else if let Some(window) = handle.get_webview_window("main") {
+        window.hide().map_err(|e| e.to_string())?;
+        show_close_hint_once(handle);
+    }
+    Ok(())
+}
+
+// Set while the close prompt is open, so clicking close again doesn't stack prompts
+static CLOSE_PROMPT_OPEN: AtomicBool = AtomicBool::new(false);
+
+/// Ask whether closing the window should quit or keep running in the tray,
+/// then whether to remember the answer (`close_behavior = "ask"`)
+///
+/// Cancelling the first prompt leaves the window open.
+fn prompt_close_behavior<R: tauri::Runtime>(handle: &tauri::AppHandle<R>) {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogResult};
+    const QUIT: &str = "Quit";
+    const HIDE: &str = "Keep running";
+
+    if CLOSE_PROMPT_OPEN.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let handle = handle.clone();
+    handle
+        .dialog()
+        .message("Quit MCP Scooter, or keep it running in the tray?")
+        .title("Close MCP Scooter")
+        .buttons(MessageDialogButtons::YesNoCancelCustom(QUIT.to_string(), HIDE.to_string(), "Cancel".to_string()))
+        .show_with_result({
+            let handle = handle.clone();
+            move |result| {
+                // Platforms differ in whether custom buttons come back as labels
+                let quit = match result {
+                    MessageDialogResult::Yes => true,
+                    MessageDialogResult::No => false,
+                    MessageDialogResult::Custom(label) if label == QUIT => true,
+                    MessageDialogResult::Custom(label) if label == HIDE => false,
+                    _ => {
+                        CLOSE_PROMPT_OPEN.store(false, Ordering::SeqCst);
+                        return;
+                    }
+                };
+                handle
+                    .dialog()
+                    .message("Do the same the next time the window is closed?")
+                    .title("Close MCP Scooter")
+                    .buttons(MessageDialogButtons::OkCancelCustom(
+                        "Don't ask again".to_string(),
+                        "Ask every time".to_string(),
+                    ))
+                    .show(move |remember| {
+                        CLOSE_PROMPT_OPEN.store(false, Ordering::SeqCst);
+                        if let Err(e) = apply_close_choice(&handle, quit, remember) {
+                            eprintln!("Warning: Failed to apply close choice: {}", e);
+                        }
+                    });
+            }
+        });
+}
+
+/// The first time the window is hidden instead of closed, tell the user the
+/// app is still running in the tray
+///
+/// On desktop, clicking the notification brings the app to the front; the
+/// notification plugin has no click callback there to do more.
+fn show_close_hint_once<R: tauri::Runtime>(handle: &tauri::AppHandle<R>) {
+    let Some(settings) = handle.try_state::<SettingsState>() else {
+        return;
+    };
+    if settings.get().close_hint_shown {
+        return;
+    }
+    notify(handle, NotificationUrgency::Low, "MCP Scooter is still running in the tray", "Use the tray icon to reopen it or quit.");
+    if let Err(e) = settings.update(|s| s.close_hint_shown = true) {
+        eprintln!("Warning: Failed to save close hint flag: {}", e);
+    }
+}
+
+// Tauri's resource dir, resolved in setup (Contents/Resources in a macOS .app)
+static RESOURCE_DIR: std::sync::OnceLock<PathBuf> = std::sync::OnceLock::new();
+
+// File name of the bundled backend binary
+#[cfg(target_os = "windows")]
+const SIDECAR_NAME: &str = "scooter.exe";
+#[cfg(not(target_os = "windows"))]
+const SIDECAR_NAME: &str = "scooter";
+
+/// Where the sidecar lives and which directory to run it from
+struct SidecarLocation {
+    path: PathBuf,
+    working_dir: PathBuf,
+}
+
+/// Directory of the running executable, if it still exists
+///
+/// On Linux, `current_exe()` of a binary that was replaced while running (as
+/// an update does) ends in " (deleted)"; the suffix is stripped so the new
+/// install next to it is found.
+fn current_exe_dir() -> Option<PathBuf> {
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => {
+            eprintln!("Warning: Failed to get current exe path: {}", e);
+            return None;
+        }
+    };
+    #[cfg(target_os = "linux")]
+    let exe = match exe.to_str().and_then(|p| p.strip_suffix(" (deleted)")) {
+        Some(path) => PathBuf::from(path),
+        None => exe,
+    };
+    exe.parent().filter(|dir| dir.is_dir()).map(|dir| dir.to_path_buf())
+}
+
+/// Locate the bundled backend binary
+///
+/// Dev builds and Windows/Linux bundles keep the sidecar beside the main exe.
+/// If the exe dir can't be determined, the resource dir is used instead.
+/// Inside a macOS .app the exe is in `Contents/MacOS/`, and bundled resources
+/// (including `appdata/`) live in the resource dir, so that is checked too.
+fn resolve_sidecar() -> Result<SidecarLocation, String> {
+    // Get the path to the sidecar binary
+    let exe_dir = current_exe_dir()
+        .or_else(|| RESOURCE_DIR.get().cloned())
+        .ok_or("Failed to determine the app install directory")?;
+
+    let mut search_dirs = vec![exe_dir.clone()];
+    if let Some(resource_dir) = RESOURCE_DIR.get() {
+        if *resource_dir != exe_dir {
+            search_dirs.push(resource_dir.clone());
+        }
+    }
+
+    let Some(path) = search_dirs.iter().map(|d| d.join(SIDECAR_NAME)).find(|p| p.exists()) else {
+        let in_app_bundle = exe_dir.ends_with("Contents/MacOS");
+        return Err(format!(
+            "Backend binary not found{} (looked in: {:?})",
+            if in_app_bundle { " inside the app bundle" } else { "" },
+            search_dirs
+        ));
+    };
+
+    // Run from whichever directory holds the bundled appdata so the backend finds it
+    let working_dir = search_dirs.iter()
+        .find(|d| d.join("appdata").is_dir())
+        .cloned()
+        .unwrap_or(exe_dir);
+
+    Ok(SidecarLocation { path, working_dir })
+}
+
+// The `backend_binary` setting, applied by `apply_settings`
+static BACKEND_BINARY: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Check that a path looks like a backend binary we can run
+fn verify_sidecar(path: &std::path::Path) -> Result<(), String> {
+    let meta = std::fs::metadata(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    if !meta.is_file() {
+        return Err(format!("{} is not a file", path.display()));
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if meta.permissions().mode() & 0o111 == 0 {
+            return Err(format!("{} is not executable", path.display()));
+        }
+    }
+    Ok(())
+}
+
+/// The backend to run: the `backend_binary` setting if set, else the sidecar
+///
+/// A custom binary still runs from the bundled appdata's directory when
+/// there is one, so it finds the same registry files.
+fn backend_location() -> Result<SidecarLocation, String> {
+    let Some(path) = BACKEND_BINARY.lock().ok().and_then(|b| b.clone()) else {
+        return resolve_sidecar();
+    };
+    verify_sidecar(&path)?;
+    eprintln!("Warning: Using unofficial backend binary {:?}; this is unsupported", path);
+    let working_dir = resolve_sidecar()
+        .map(|s| s.working_dir)
+        .unwrap_or_else(|_| path.parent().map(PathBuf::from).unwrap_or_default());
+    Ok(SidecarLocation { path, working_dir })
+}
+
+/// Pick a custom backend binary with a file dialog and use it from now on
+///
+/// Returns the selected path, or None if the dialog was cancelled. Offers
+/// to restart the gateway so the new binary takes effect.
+#[tauri::command]
+async fn select_backend_binary(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.dialog().file().set_title("Select backend binary").pick_file(move |path| {
+        let _ = tx.send(path);
+    });
+    let Some(picked) = rx.await.map_err(|_| "File dialog closed unexpectedly".to_string())? else {
+        return Ok(None);
+    };
+    let path = picked.into_path().map_err(|e| format!("Invalid selection: {}", e))?;
+    verify_sidecar(&path)?;
+
+    let path_str = path.to_string_lossy().to_string();
+    let state = app.state::<SettingsState>();
+    let updated = state.update(|s| s.backend_binary = Some(path_str.clone()))?;
+    apply_settings(&app, &updated);
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.dialog()
+        .message(
+            "Unofficial backend binaries are unsupported and may not work with this version of MCP Scooter. \
+             Restart the gateway now to use it?",
+        )
+        .title("Custom backend")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom("Restart".to_string(), "Later".to_string()))
+        .show(move |restart| {
+            let _ = tx.send(restart);
+        });
+    if rx.await.unwrap_or(false) {
+        restart_backend(&app).await?;
+    }
+    Ok(Some(path_str))
+}
+
+/// Split a command line into arguments, honoring single and double quotes
+fn split_command_line(line: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote: Option<char> = None;
+
+    for c in line.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            None if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            None => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+
+    if quote.is_some() {
+        return Err(format!("Unterminated quote in command line: {}", line));
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+/// Backend command from `SCOOTER_BACKEND_CMD`, for running a dev build
+/// (e.g. `SCOOTER_BACKEND_CMD="go run ./cmd/scooter"`) instead of the sidecar
+fn backend_command_override() -> Result<Option<Vec<String>>, String> {
+    match std::env::var("SCOOTER_BACKEND_CMD") {
+        Ok(line) if !line.trim().is_empty() => split_command_line(&line).map(Some),
+        _ => Ok(None),
+    }
+}
+
+// The `sandbox_command` setting, applied by `apply_settings`
+static SANDBOX_COMMAND: Mutex<Option<String>> = Mutex::new(None);
+
+// The `max_backend_memory_mb` setting (0 when unset) and whether the kernel
+// should enforce it (`memory_limit_cgroup`)
+static MAX_BACKEND_MEMORY_MB: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+static MEMORY_LIMIT_CGROUP: AtomicBool = AtomicBool::new(false);
+
+/// Find a program the way the shell would: as given if it's a path, else
+/// on `PATH`
+#[cfg(target_os = "linux")]
+fn find_program(program: &str) -> Option<PathBuf> {
+    let path = std::path::Path::new(program);
+    if path.components().count() > 1 {
+        return path.is_file().then(|| path.to_path_buf());
+    }
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths).map(|dir| dir.join(program)).find(|p| p.is_file())
+}
+
+/// Command running the sidecar, wrapped in the `sandbox_command` setting
+/// (e.g. `firejail --quiet` or `systemd-run --user --scope`) if one is set
+///
+/// Linux only. If the wrapper can't be parsed or isn't installed, the
+/// sidecar runs directly with a warning. The managed pid is then the
+/// wrapper's, so prefer wrappers that exec or forward signals to the backend.
+#[cfg(target_os = "linux")]
+fn sandboxed_command(sidecar: &std::path::Path) -> Command {
+    let wrapper = SANDBOX_COMMAND.lock().ok().and_then(|w| w.clone()).filter(|w| !w.trim().is_empty());
+    let Some(wrapper) = wrapper else {
+        return memory_limited_command(sidecar);
+    };
+    let args = match split_command_line(&wrapper) {
+        Ok(args) if !args.is_empty() => args,
+        Ok(_) => return background_command(sidecar),
+        Err(e) => {
+            eprintln!("Warning: Ignoring invalid sandbox command '{}': {}", wrapper, e);
+            return background_command(sidecar);
+        }
+    };
+    let Some(program) = find_program(&args[0]) else {
+        eprintln!("Warning: Sandbox wrapper '{}' not found, starting the backend unsandboxed", args[0]);
+        return background_command(sidecar);
+    };
+    log_lifecycle(LifecycleEventKind::Spawn, format!("Starting backend under sandbox wrapper {}", args[0]));
+    let mut cmd = background_command(program);
+    cmd.args(&args[1..]).arg(sidecar);
+    cmd
+}
+
+/// Command running the sidecar in a cgroup capped at `max_backend_memory_mb`,
+/// when `memory_limit_cgroup` is on and systemd-run is available
+#[cfg(target_os = "linux")]
+fn memory_limited_command(sidecar: &std::path::Path) -> Command {
+    let limit_mb = MAX_BACKEND_MEMORY_MB.load(Ordering::Relaxed);
+    if limit_mb == 0 || !MEMORY_LIMIT_CGROUP.load(Ordering::Relaxed) {
+        return background_command(sidecar);
+    }
+    let Some(systemd_run) = find_program("systemd-run") else {
+        eprintln!("Warning: systemd-run not found, the backend memory limit is only monitored");
+        return background_command(sidecar);
+    };
+    log_lifecycle(LifecycleEventKind::Spawn, format!("Starting backend with a {} MB memory limit", limit_mb));
+    let mut cmd = background_command(systemd_run);
+    cmd.args(["--user", "--scope", "--quiet", "-p"])
+        .arg(format!("MemoryMax={}M", limit_mb))
+        .arg(sidecar);
+    cmd
+}
+
+#[cfg(not(target_os = "linux"))]
+fn sandboxed_command(sidecar: &std::path::Path) -> Command {
+    background_command(sidecar)
+}
+
+// The `env_inheritance` setting, applied by `apply_settings`
+static ENV_INHERITANCE: Mutex<EnvInheritance> = Mutex::new(EnvInheritance::Full);
+
+// Passed on even with `env_inheritance: none`: what a process needs to start
+// and the backend needs to find its default data dir
+#[cfg(target_os = "windows")]
+const ESSENTIAL_ENV_VARS: &[&str] = &["SystemRoot", "SystemDrive", "windir", "USERPROFILE", "APPDATA", "LOCALAPPDATA"];
+// The XDG/D-Bus ones let `systemd-run --user` wrappers reach the user's service manager
+#[cfg(not(target_os = "windows"))]
+const ESSENTIAL_ENV_VARS: &[&str] = &["HOME", "XDG_RUNTIME_DIR", "DBUS_SESSION_BUS_ADDRESS"];
+
+// Further variables passed on with `env_inheritance: minimal`, besides `SCOOTER_*`
+const MINIMAL_ENV_VARS: &[&str] = &["PATH", "HOME", "USERPROFILE", "TEMP", "TMP", "TMPDIR"];
+
+/// Restrict the environment a backend command inherits, per `env_inheritance`
+///
+/// Variables set on the command afterwards (e.g. `SCOOTER_CONFIG_DIR`)
+/// are passed on either way.
+fn restrict_backend_env(cmd: &mut Command) {
+    let inheritance = ENV_INHERITANCE.lock().map(|i| *i).unwrap_or_default();
+    let names: Vec<&str> = match inheritance {
+        EnvInheritance::Full => return,
+        EnvInheritance::Minimal => ESSENTIAL_ENV_VARS.iter().chain(MINIMAL_ENV_VARS).copied().collect(),
+        EnvInheritance::None => ESSENTIAL_ENV_VARS.to_vec(),
+    };
+    let mut kept: Vec<(std::ffi::OsString, std::ffi::OsString)> = names.into_iter()
+        .filter_map(|name| std::env::var_os(name).map(|value| (name.into(), value)))
+        .collect();
+    if inheritance == EnvInheritance::Minimal {
+        kept.extend(std::env::vars_os().filter(|(name, _)| name.to_string_lossy().starts_with("SCOOTER_")));
+    }
+    cmd.env_clear().envs(kept);
+}
+
+/// The command that starts the backend, ready to spawn
+fn backend_command() -> Result<Command, String> {
+    // On Windows, the console window is hidden by background_command
+    let mut cmd = if let Some(args) = backend_command_override()? {
+        // Dev override: run from the current dir and keep the output visible
+        println!("Backend command from SCOOTER_BACKEND_CMD: {:?}", args);
+        let mut cmd = background_command(&args[0]);
+        cmd.args(&args[1..]);
+        cmd
+    } else {
+        let sidecar = backend_location()?;
+        let mut cmd = sandboxed_command(&sidecar.path);
+        cmd.current_dir(&sidecar.working_dir) // Set working directory so it finds appdata
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped()); // Kept for startup failure reports
+        cmd
+    };
+    restrict_backend_env(&mut cmd);
+    if let Some(dir) = configured_backend_data_dir() {
+        cmd.env("SCOOTER_CONFIG_DIR", dir);
+    }
+    Ok(cmd)
+}
+
+// Lock files a backend may leave in its data dir, holding the owner's pid
+const BACKEND_LOCK_FILES: &[&str] = &["scooter.lock", "scooter.pid"];
+
+/// Remove lock files left in a backend data dir by a backend that died,
+/// which would otherwise make the next one refuse to start
+///
+/// A lock only counts as stale if no process with the pid in it is running
+/// (whatever its name, since a custom binary, dev command or wrapper runs
+/// under another one) or, when it holds no pid, if no managed backend is
+/// running. A lock that can't be read (e.g. held open exclusively on
+/// Windows) is left alone.
+fn remove_stale_locks(dir: &std::path::Path) {
+    for name in BACKEND_LOCK_FILES {
+        let path = dir.join(name);
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let held = match contents.trim().parse::<u32>() {
+            Ok(owner) => {
+                let pid = Pid::from(owner as usize);
+                let mut sys = System::new();
+                sys.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[pid]), true);
+                sys.process(pid).is_some()
+            }
+            Err(_) => !managed_backend_pids().is_empty(),
+        };
+        if held {
+            continue;
+        }
+        eprintln!("Warning: Removing stale backend lock file {:?} (owner '{}' is not running)", path, contents.trim());
+        log_lifecycle(LifecycleEventKind::Spawn, format!("Removed stale lock file {}", name));
+        if let Err(e) = std::fs::remove_file(&path) {
+            eprintln!("Warning: Failed to remove {:?}: {}", path, e);
+        }
+    }
+}
+
+/// Spawn the scooter backend process
+fn spawn_backend() -> Result<Child, String> {
+    if let Some(dir) = backend_data_dir() {
+        remove_stale_locks(&dir);
+    }
+    let mut cmd = backend_command()?;
+    let mut elevated = false;
+    if RUN_ELEVATED.load(Ordering::SeqCst) && !ELEVATION_DECLINED.load(Ordering::SeqCst) {
+        match elevated_command(&cmd) {
+            Some(wrapped) => {
+                cmd = wrapped;
+                cmd.stdout(Stdio::null()).stderr(Stdio::piped());
+                elevated = true;
+            }
+            None => eprintln!("Warning: No elevation prompt available, starting the backend unelevated"),
+        }
+    }
+    let mut child = cmd.spawn()
+        .map_err(|e| format!("Failed to spawn backend: {}", e))?;
+    LAUNCHED_ELEVATED.store(elevated, Ordering::SeqCst);
+    if let Some(stderr) = child.stderr.take() {
+        capture_stderr(stderr);
+    }
+    log_lifecycle(LifecycleEventKind::Spawn, format!("Started backend (pid {}){}", child.id(), if elevated { " elevated" } else { "" }));
+    
+    Ok(child)
+}
+
+// The `run_elevated` setting, applied by `apply_settings`
+static RUN_ELEVATED: AtomicBool = AtomicBool::new(false);
+
+// Set when the elevation prompt was cancelled; the backend then runs
+// unelevated until `run_elevated` is switched on again
+static ELEVATION_DECLINED: AtomicBool = AtomicBool::new(false);
+
+// Whether the managed backend was started through the elevation prompt
+static LAUNCHED_ELEVATED: AtomicBool = AtomicBool::new(false);
+
+// Exit code of the Windows elevation wrapper when UAC was cancelled
+const ERROR_CANCELLED: i32 = 1223;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ElevationCancelled {
+    pub message: String,
+}
+
+/// Quote an argument for `sh`
+#[cfg(target_os = "macos")]
+fn sh_quote(arg: &std::ffi::OsStr) -> String {
+    format!("'{}'", arg.to_string_lossy().replace('\'', "'\\''"))
+}
+
+/// The backend command rewritten to go through pkexec
+///
+/// pkexec resets the environment and working dir, so both are passed on
+/// through `env`. It execs the backend, which then runs as root.
+#[cfg(target_os = "linux")]
+fn elevated_command(cmd: &Command) -> Option<Command> {
+    let pkexec = find_program("pkexec")?;
+    let env = find_program("env")?;
+    let mut elevated = background_command(pkexec);
+    elevated.arg(env);
+    if let Some(dir) = cmd.get_current_dir() {
+        let mut chdir = std::ffi::OsString::from("--chdir=");
+        chdir.push(dir);
+        elevated.arg(chdir);
+    }
+    // Only variables set on the command; removed ones (None) need no passing on
+    for (name, value) in cmd.get_envs().filter_map(|(name, value)| Some((name, value?))) {
+        let mut var = name.to_os_string();
+        var.push("=");
+        var.push(value);
+        elevated.arg(var);
+    }
+    elevated.arg(cmd.get_program()).args(cmd.get_args());
+    Some(elevated)
+}
+
+/// The backend command rewritten to run via `do shell script ... with
+/// administrator privileges`, which shows the admin password dialog
+#[cfg(target_os = "macos")]
+fn elevated_command(cmd: &Command) -> Option<Command> {
+    let mut script = String::new();
+    if let Some(dir) = cmd.get_current_dir() {
+        script.push_str(&format!("cd {} && ", sh_quote(dir.as_os_str())));
+    }
+    script.push_str("exec /usr/bin/env");
+    for (name, value) in cmd.get_envs().filter_map(|(name, value)| Some((name, value?))) {
+        script.push_str(&format!(" {}={}", name.to_string_lossy(), sh_quote(value)));
+    }
+    for arg in std::iter::once(cmd.get_program()).chain(cmd.get_args()) {
+        script.push(' ');
+        script.push_str(&sh_quote(arg));
+    }
+    let applescript = format!(
+        "do shell script \"{}\" with administrator privileges",
+        script.replace('\\', "\\\\").replace('"', "\\\"")
+    );
+    let mut elevated = background_command("/usr/bin/osascript");
+    elevated.arg("-e").arg(applescript);
+    Some(elevated)
+}
+
+/// The backend command rewritten to start through a UAC prompt
+///
+/// PowerShell's `Start-Process -Verb RunAs` does the elevation; the inner
+/// script (passed encoded to avoid another layer of quoting) sets the
+/// environment and working dir, which an elevated process doesn't inherit.
+#[cfg(target_os = "windows")]
+fn elevated_command(cmd: &Command) -> Option<Command> {
+    use base64::Engine;
+    let quote = |arg: &std::ffi::OsStr| format!("'{}'", arg.to_string_lossy().replace('\'', "''"));
+    let mut inner = String::new();
+    for (name, value) in cmd.get_envs().filter_map(|(name, value)| Some((name, value?))) {
+        inner.push_str(&format!("${{env:{}}} = {}; ", name.to_string_lossy(), quote(value)));
+    }
+    if let Some(dir) = cmd.get_current_dir() {
+        inner.push_str(&format!("Set-Location -LiteralPath {}; ", quote(dir.as_os_str())));
+    }
+    let args: Vec<String> = cmd.get_args().map(quote).collect();
+    inner.push_str(&format!("& {} {}; exit $LASTEXITCODE", quote(cmd.get_program()), args.join(" ")));
+    let utf16: Vec<u8> = inner.encode_utf16().flat_map(u16::to_le_bytes).collect();
+    let encoded = base64::engine::general_purpose::STANDARD.encode(utf16);
+    // Start-Process throws when the prompt is cancelled; report that as ERROR_CANCELLED
+    let outer = format!(
+        "try {{ $p = Start-Process powershell -Verb RunAs -Wait -PassThru -WindowStyle Hidden \
+         -ArgumentList '-NoProfile','-EncodedCommand','{}' -ErrorAction Stop }} catch {{ exit {} }}; exit $p.ExitCode",
+        encoded, ERROR_CANCELLED
+    );
+    let mut elevated = background_command("powershell");
+    elevated.args(["-NoProfile", "-NonInteractive", "-Command", &outer]);
+    Some(elevated)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn elevated_command(_cmd: &Command) -> Option<Command> {
+    None
+}
+
+/// Whether an elevated launch ended because the prompt was cancelled or refused
+fn elevation_cancelled(code: Option<i32>) -> bool {
+    if cfg!(target_os = "linux") {
+        // pkexec: 126 when the dialog was dismissed, 127 when not authorized
+        matches!(code, Some(126 | 127))
+    } else if cfg!(target_os = "macos") {
+        // osascript reports "User canceled. (-128)"
+        code == Some(1) && backend_stderr_tail().contains("(-128)")
+    } else {
+        cfg!(target_os = "windows") && code == Some(ERROR_CANCELLED)
+    }
+}
+
+/// Report a cancelled elevation prompt and start the backend unelevated
+fn fall_back_unelevated<R: tauri::Runtime>(handle: &tauri::AppHandle<R>) {
+    ELEVATION_DECLINED.store(true, Ordering::SeqCst);
+    let message = "Elevation was cancelled, so the backend runs without elevated privileges".to_string();
+    eprintln!("Warning: {}", message);
+    log_lifecycle(LifecycleEventKind::Spawn, message.clone());
+    notify(handle, NotificationUrgency::Normal, "MCP Scooter", &message);
+    let _ = handle.emit("elevation-cancelled", ElevationCancelled { message });
+    match spawn_backend() {
+        Ok(child) => store_backend(child),
+        Err(e) => eprintln!("Error: Failed to start the backend unelevated: {}", e),
+    }
+}
+
+// Last lines the backend wrote to stderr, for startup failure reports
+static BACKEND_STDERR: Mutex<std::collections::VecDeque<String>> = Mutex::new(std::collections::VecDeque::new());
+
+// How many stderr lines are kept
+const STDERR_TAIL_LINES: usize = 40;
+
+// When the current backend's stderr closed, i.e. about when it exited
+static BACKEND_STDERR_CLOSED_AT: Mutex<Option<std::time::Instant>> = Mutex::new(None);
+
+// A backend exiting this soon after spawning failed to start
+const STARTUP_FAILURE_WINDOW: Duration = Duration::from_secs(5);
+
+/// Drain a new backend's stderr on a background thread, keeping the tail
+///
+/// The pipe must be read for as long as the backend runs, or it would block
+/// once the pipe buffer fills.
+fn capture_stderr(stderr: std::process::ChildStderr) {
+    if let Ok(mut tail) = BACKEND_STDERR.lock() {
+        tail.clear();
+    }
+    if let Ok(mut closed) = BACKEND_STDERR_CLOSED_AT.lock() {
+        *closed = None;
+    }
+    std::thread::spawn(move || {
+        use std::io::BufRead;
+        let reader = std::io::BufReader::new(stderr);
+        for line in reader.split(b'\n').map_while(Result::ok) {
+            if let Ok(mut tail) = BACKEND_STDERR.lock() {
+                if tail.len() == STDERR_TAIL_LINES {
+                    tail.pop_front();
+                }
+                tail.push_back(String::from_utf8_lossy(&line).trim_end().to_string());
+            }
+        }
+        if let Ok(mut closed) = BACKEND_STDERR_CLOSED_AT.lock() {
+            *closed = Some(std::time::Instant::now());
+        }
+    });
+}
+
+/// The last lines the current backend wrote to stderr
+fn backend_stderr_tail() -> String {
+    BACKEND_STDERR.lock()
+        .map(|tail| tail.iter().cloned().collect::<Vec<_>>().join("\n"))
+        .unwrap_or_default()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BackendStartupFailed {
+    pub code: Option<i32>,
+    /// How long the backend ran before exiting
+    pub ran_for_ms: u64,
+    /// The last lines the backend wrote to stderr
+    pub stderr_tail: String,
+}
+
+/// If a backend that just exited only ran for a moment, report its stderr
+/// via `backend-startup-failed` so the UI can show why it didn't start
+fn report_startup_failure<R: tauri::Runtime>(
+    handle: &tauri::AppHandle<R>,
+    started_at: Option<std::time::Instant>,
+    code: Option<i32>,
+) {
+    let Some(started_at) = started_at else {
+        return;
+    };
+    let exited_at = BACKEND_STDERR_CLOSED_AT.lock().ok()
+        .and_then(|c| *c)
+        .unwrap_or_else(std::time::Instant::now);
+    let ran_for = exited_at.saturating_duration_since(started_at);
+    if ran_for >= STARTUP_FAILURE_WINDOW {
+        return;
+    }
+    let stderr_tail = backend_stderr_tail();
+    eprintln!("Error: Backend exited {}ms after starting: {}", ran_for.as_millis(), stderr_tail);
+    let _ = handle.emit("backend-startup-failed", BackendStartupFailed {
+        code,
+        ran_for_ms: ran_for.as_millis() as u64,
+        stderr_tail,
+    });
+}
+
+/// Store a newly spawned backend as the managed process.
+///
+/// Any previously stored child is killed and reaped first, so that at most
+/// one managed backend is ever alive.
+fn store_backend(child: Child) {
+    let backend = ManagedBackend {
+        child: Some(child),
+        control_port: CONTROL_PORT.load(Ordering::Relaxed),
+        mcp_port: MCP_PORT.load(Ordering::Relaxed),
+        phase: BackendPhase::Starting,
+        status: None,
+    };
+    let old = BACKEND_INSTANCES.lock().ok()
+        .and_then(|mut guard| guard.insert(PRIMARY_INSTANCE.to_string(), backend))
+        .and_then(|old| old.child);
+    // Killed and reaped after the lock is released, see BACKEND_INSTANCES
+    if let Some(mut old) = old {
+        let _ = old.kill();
+        let _ = old.wait();
+    }
+    if let Ok(mut started) = BACKEND_STARTED_AT.lock() {
+        *started = Some(std::time::Instant::now());
+    }
+    BACKEND_CRASHED.store(false, Ordering::SeqCst);
+    BACKEND_STOPPED.store(false, Ordering::SeqCst);
+}
+
+/// Gracefully stop the backend, then spawn a fresh one and reload the UI
+///
+/// Refuses to run while an update is being installed.
+async fn restart_backend<R: tauri::Runtime>(handle: &tauri::AppHandle<R>) -> Result<(), String> {
+    let _lifecycle = begin_lifecycle(LifecycleState::Restarting)?;
+    log_lifecycle(LifecycleEventKind::Restart, "Restarting gateway");
+
+    // 1. Tell the backend to shutdown
+    let client = http_client()?;
+    let _ = client.post(endpoint_url(|e| e.shutdown.as_str())).send().await;
+
+    // 2. Wait a bit for it to exit
+    tokio::time::sleep(Duration::from_millis(1000)).await;
+
+    // 3. Kill it just in case it's still hanging
+    kill_backend();
+
+    // 4. Spawn a new one
+    let result = spawn_backend().map(|child| {
+        store_backend(child);
+        track_offline(true);
+        println!("Backend process restarted successfully");
+    });
+
+    // 5. Reload the frontend window if it exists
+    if let Some(window) = handle.get_webview_window("main") {
+        let _ = window.eval("window.location.reload()");
+    }
+
+    result
+}
+
+/// What a gateway restart does, as reported by `restart_gateway`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RestartPlan {
+    pub dry_run: bool,
+    /// Pid of the backend that would be stopped
+    pub current_pid: Option<u32>,
+    pub shutdown_endpoint: String,
+    /// Whether the backend answers, so the graceful shutdown can work;
+    /// otherwise it's killed after the grace period
+    pub shutdown_reachable: bool,
+    pub program: Option<String>,
+    pub args: Vec<String>,
+    pub working_dir: Option<String>,
+    /// Environment variables set for the backend on top of ours; unless
+    /// `env_inheritance` is full, the only ones it gets
+    pub env: Vec<(String, String)>,
+    /// Why the backend couldn't be started, if that's already known
+    pub error: Option<String>,
+    pub steps: Vec<String>,
+}
+
+/// What restarting the primary backend, or a secondary `instance`, would do
+async fn plan_restart(dry_run: bool, instance: Option<&BackendInstance>) -> RestartPlan {
+    let port = instance.map_or_else(|| CONTROL_PORT.load(Ordering::Relaxed), |i| i.control_port);
+    let shutdown_endpoint = endpoint_url_on(port, |e| e.shutdown.as_str());
+    let shutdown_reachable = match http_client() {
+        Ok(client) => ping_backend_on(&client, port).await,
+        Err(_) => false,
+    };
+    let current_pid = instance_pid(instance.map_or(PRIMARY_INSTANCE, |i| i.id.as_str()));
+    let command = match instance {
+        Some(instance) => instance_command(instance),
+        None => backend_command(),
+    };
+
+    let mut steps = Vec::new();
+    steps.push(if shutdown_reachable {
+        format!("POST {} to ask the backend to shut down", shutdown_endpoint)
+    } else {
+        "Skip the graceful shutdown: the backend isn't answering".to_string()
+    });
+    steps.push("Wait 1s for it to exit".to_string());
+    steps.push(match current_pid {
+        Some(pid) => format!("Kill backend pid {} if it's still running", pid),
+        None => "No managed backend to kill".to_string(),
+    });
+    let (program, args, working_dir, env, error) = match &command {
+        Ok(cmd) => {
+            let lossy = |s: &std::ffi::OsStr| s.to_string_lossy().to_string();
+            let program = lossy(cmd.get_program());
+            steps.push(format!("Start {}", program));
+            (
+                Some(program),
+                cmd.get_args().map(lossy).collect(),
+                cmd.get_current_dir().map(|d| d.to_string_lossy().to_string()),
+                cmd.get_envs().filter_map(|(k, v)| Some((lossy(k), lossy(v?)))).collect(),
+                None,
+            )
+        }
+        Err(e) => {
+            steps.push(format!("Start the backend, which would fail: {}", e));
+            (None, Vec::new(), None, Vec::new(), Some(e.clone()))
+        }
+    };
+    if instance.is_none() {
+        steps.push("Reload the dashboard".to_string());
+    }
+
+    RestartPlan {
+        dry_run,
+        current_pid,
+        shutdown_endpoint,
+        shutdown_reachable,
+        program,
+        args,
+        working_dir,
+        env,
+        error,
+        steps,
+    }
+}
+
+/// Restart the gateway, or with `dry_run` only report what a restart would do
+///
+/// Restarts the primary backend unless a secondary `instance` id is given.
+#[tauri::command]
+async fn restart_gateway(app: tauri::AppHandle, dry_run: Option<bool>, instance: Option<String>) -> Result<RestartPlan, String> {
+    let dry_run = dry_run.unwrap_or(false);
+    let Some(id) = instance.as_deref().filter(|id| *id != PRIMARY_INSTANCE) else {
+        let plan = plan_restart(dry_run, None).await;
+        if !dry_run {
+            restart_backend(&app).await?;
+        }
+        return Ok(plan);
+    };
+    let instance = secondary_instance(&app, id)?;
+    let plan = plan_restart(dry_run, Some(&instance)).await;
+    if !dry_run {
+        stop_instance(&instance).await;
+        spawn_instance(&instance)?;
+        update_tray(&app, &last_status());
+    }
+    Ok(plan)
+}
+
+/// The `instances` entry with this id
+fn secondary_instance<R: tauri::Runtime>(handle: &tauri::AppHandle<R>, id: &str) -> Result<BackendInstance, String> {
+    handle.try_state::<SettingsState>()
+        .and_then(|s| s.get().instances.into_iter().find(|i| i.id == id))
+        .ok_or_else(|| format!("No backend instance '{}'", id))
+}
+
+/// Ports the backend will listen on with this data dir: `control_port` and
+/// `mcp_port` from its settings.yaml, else the backend's defaults
+///
+/// The file is a flat `settings:` map, so a line scan is enough here.
+fn configured_backend_ports(dir: &std::path::Path) -> (u16, u16) {
+    let defaults = Settings::default();
+    let (mut control_port, mut mcp_port) = (defaults.control_port, defaults.mcp_port);
+    let contents = std::fs::read_to_string(dir.join("settings.yaml")).unwrap_or_default();
+    for line in contents.lines() {
+        let Some((key, value)) = line.trim().split_once(':') else {
+            continue;
+        };
+        // The backend treats 0 (or a missing key) as "use the default"
+        let Ok(port @ 1..) = value.trim().trim_matches(['"', '\'']).parse::<u16>() else {
+            continue;
+        };
+        match key.trim() {
+            "control_port" => control_port = port,
+            "mcp_port" => mcp_port = port,
+            _ => {}
+        }
+    }
+    (control_port, mcp_port)
+}
+
+/// Refuse to start an instance whose ports differ from those its backend
+/// will actually use, which would leave it polled on the wrong port
+fn check_instance_ports(instance: &BackendInstance) -> Result<(), String> {
+    let (control_port, mcp_port) = configured_backend_ports(std::path::Path::new(&instance.data_dir));
+    if (control_port, mcp_port) != (instance.control_port, instance.mcp_port) {
+        return Err(format!(
+            "Instance '{}' is set to ports {}/{}, but settings.yaml in {} gives {}/{}; set control_port and mcp_port there to match",
+            instance.id, instance.control_port, instance.mcp_port, instance.data_dir, control_port, mcp_port
+        ));
+    }
+    Ok(())
+}
+
+/// The command that starts a secondary instance on its own data dir
+fn instance_command(instance: &BackendInstance) -> Result<Command, String> {
+    check_instance_ports(instance)?;
+    let mut cmd = backend_command()?;
+    // Only the primary's stderr is drained; an unread pipe would block this one
+    cmd.env("SCOOTER_CONFIG_DIR", &instance.data_dir).stderr(Stdio::null());
+    Ok(cmd)
+}
+
+/// Start a secondary instance, killing a previous process for it if any
+fn spawn_instance(instance: &BackendInstance) -> Result<(), String> {
+    remove_stale_locks(std::path::Path::new(&instance.data_dir));
+    let child = instance_command(instance)?.spawn()
+        .map_err(|e| format!("Failed to spawn backend instance {}: {}", instance.id, e))?;
+    log_lifecycle(LifecycleEventKind::Spawn, format!("Started instance {} (pid {})", instance.id, child.id()));
+    let backend = ManagedBackend {
+        child: Some(child),
+        control_port: instance.control_port,
+        mcp_port: instance.mcp_port,
+        phase: BackendPhase::Starting,
+        status: None,
+    };
+    let old = BACKEND_INSTANCES.lock().ok()
+        .and_then(|mut guard| guard.insert(instance.id.clone(), backend))
+        .and_then(|old| old.child);
+    // Killed and reaped after the lock is released, see BACKEND_INSTANCES
+    if let Some(mut old) = old {
+        let _ = old.kill();
+        let _ = old.wait();
+    }
+    Ok(())
+}
+
+/// Start the secondary instances from the `instances` setting
+fn spawn_instances(settings: &Settings) {
+    for instance in &settings.instances {
+        if let Err(e) = spawn_instance(instance) {
+            eprintln!("Warning: {}", e);
+        }
+    }
+}
+
+/// Kill a secondary instance, keeping it listed as stopped
+fn kill_instance(id: &str) {
+    let child = BACKEND_INSTANCES.lock().ok().and_then(|mut guard| {
+        let backend = guard.get_mut(id)?;
+        backend.phase = BackendPhase::Stopped;
+        backend.status = None;
+        backend.child.take()
+    });
+    if let Some(mut child) = child {
+        log_lifecycle(LifecycleEventKind::Shutdown, format!("Stopped instance {} (pid {})", id, child.id()));
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+/// Ask a secondary instance to shut down, killing it after a grace period
+async fn stop_instance(instance: &BackendInstance) {
+    if let Ok(client) = http_client() {
+        let _ = client.post(endpoint_url_on(instance.control_port, |e| e.shutdown.as_str())).send().await;
+        tokio::time::sleep(Duration::from_millis(1000)).await;
+    }
+    kill_instance(&instance.id);
+}
+
+/// Kill every managed backend, primary and secondary
+fn kill_all_backends() {
+    kill_backend();
+    let ids: Vec<String> = BACKEND_INSTANCES.lock()
+        .map(|guard| guard.keys().cloned().collect())
+        .unwrap_or_default();
+    for id in ids {
+        kill_instance(&id);
+    }
+}
+
+/// Status of the backend whose control API is on `port`
+async fn fetch_status_on(client: &reqwest::Client, port: u16) -> Option<AppStatus> {
+    let resp = client.get(endpoint_url_on(port, |e| e.status.as_str()))
+        .timeout(Duration::from_secs(5))
+        .send()
+        .await
+        .ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
+    resp.json::<AppStatus>().await.ok()
+}
+
+/// Reap exited secondary instances and refresh the status of the others
+///
+/// Unlike the primary, a crashed instance isn't restarted automatically; it
+/// is reported and left down until started again.
+async fn poll_instances<R: tauri::Runtime>(handle: &tauri::AppHandle<R>, client: &reqwest::Client) {
+    let instances = handle.try_state::<SettingsState>().map(|s| s.get().instances).unwrap_or_default();
+    let mut changed = false;
+    for instance in &instances {
+        let exit = {
+            let Ok(mut guard) = BACKEND_INSTANCES.lock() else {
+                return;
+            };
+            let Some(backend) = guard.get_mut(&instance.id) else {
+                continue;
+            };
+            let Some(child) = backend.child.as_mut() else {
+                continue;
+            };
+            match child.try_wait() {
+                Ok(Some(exit)) => {
+                    backend.child = None;
+                    backend.status = None;
+                    backend.phase = if exit.code() == Some(0) { BackendPhase::Stopped } else { BackendPhase::Crashed };
+                    Some(exit.code())
+                }
+                _ => None,
+            }
+        };
+        if let Some(code) = exit {
+            changed = true;
+            if code == Some(0) {
+                log_lifecycle(LifecycleEventKind::Shutdown, format!("Instance {} exited cleanly", instance.id));
+            } else {
+                log_lifecycle(LifecycleEventKind::Crash, format!("Instance {} exited with code {:?}", instance.id, code));
+                notify(handle, NotificationUrgency::Critical, "MCP Scooter", &format!("Gateway {} crashed. Use Start in the tray menu to bring it back.", instance.display_name()));
+            }
+            continue;
+        }
+
+        let status = fetch_status_on(client, instance.control_port).await;
+        if let Ok(mut guard) = BACKEND_INSTANCES.lock() {
+            if let Some(backend) = guard.get_mut(&instance.id).filter(|b| b.child.is_some()) {
+                let phase = if status.is_some() { BackendPhase::Running } else { BackendPhase::Starting };
+                changed |= backend.phase != phase || backend.status != status;
+                backend.phase = phase;
+                backend.status = status;
+            }
+        }
+    }
+    if changed {
+        update_tray(handle, &last_status());
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InstanceInfo {
+    pub id: String,
+    pub name: String,
+    pub phase: BackendPhase,
+    pub pid: Option<u32>,
+    pub control_port: u16,
+    pub mcp_port: u16,
+    pub health: HealthSummary,
+}
+
+/// Ports a managed backend was started with, which may predate a settings change
+fn instance_ports(id: &str) -> Option<(u16, u16)> {
+    BACKEND_INSTANCES.lock().ok()?.get(id).map(|b| (b.control_port, b.mcp_port))
+}
+
+/// State of a secondary instance; never started counts as stopped
+fn instance_info(instance: &BackendInstance) -> InstanceInfo {
+    let (phase, status) = BACKEND_INSTANCES.lock().ok()
+        .and_then(|guard| guard.get(&instance.id).map(|b| (b.phase, b.status.clone())))
+        .unwrap_or((BackendPhase::Stopped, None));
+    let (control_port, mcp_port) = instance_ports(&instance.id).unwrap_or((instance.control_port, instance.mcp_port));
+    InstanceInfo {
+        id: instance.id.clone(),
+        name: instance.display_name().to_string(),
+        phase,
+        pid: instance_pid(&instance.id),
+        control_port,
+        mcp_port,
+        health: compute_health(&status),
+    }
+}
+
+/// The primary backend followed by the secondary instances
+#[tauri::command]
+async fn list_instances(app: tauri::AppHandle) -> Result<Vec<InstanceInfo>, String> {
+    let settings = app.state::<SettingsState>().get();
+    let (control_port, mcp_port) = instance_ports(PRIMARY_INSTANCE).unwrap_or((settings.control_port, settings.mcp_port));
+    let mut instances = vec![InstanceInfo {
+        id: PRIMARY_INSTANCE.to_string(),
+        name: "Primary".to_string(),
+        phase: backend_phase(),
+        pid: managed_backend_pid(),
+        control_port,
+        mcp_port,
+        health: compute_health(&last_status()),
+    }];
+    instances.extend(settings.instances.iter().map(instance_info));
+    Ok(instances)
+}
+
+/// Start a gateway that isn't running: the primary unless an `instance` id
+/// is given
+#[tauri::command]
+async fn start_gateway(app: tauri::AppHandle, instance: Option<String>) -> Result<(), String> {
+    match instance.as_deref().filter(|id| *id != PRIMARY_INSTANCE) {
+        None => {
+            if managed_backend_pid().is_some() {
+                return Err("The gateway is already running".to_string());
+            }
+            start_crashed_backend()?;
+        }
+        Some(id) => {
+            if instance_pid(id).is_some() {
+                return Err(format!("Gateway {} is already running", id));
+            }
+            spawn_instance(&secondary_instance(&app, id)?)?;
+        }
+    }
+    update_tray(&app, &last_status());
+    Ok(())
+}
+
+/// Stop a gateway: the primary unless an `instance` id is given
+///
+/// A stopped gateway stays down (it isn't treated as a crash) until started
+/// again with `start_gateway` or from the tray.
+#[tauri::command]
+async fn stop_gateway(app: tauri::AppHandle, instance: Option<String>) -> Result<(), String> {
+    match instance.as_deref().filter(|id| *id != PRIMARY_INSTANCE) {
+        None => {
+            let _lifecycle = begin_lifecycle(LifecycleState::Restarting)?;
+            let _ = http_client()?.post(endpoint_url(|e| e.shutdown.as_str())).send().await;
+            tokio::time::sleep(Duration::from_millis(1000)).await;
+            kill_backend();
+            BACKEND_STOPPED.store(true, Ordering::SeqCst);
+        }
+        Some(id) => stop_instance(&secondary_instance(&app, id)?).await,
+    }
+    update_tray(&app, &last_status());
+    Ok(())
+}
+
+/// Menu id for a per-instance tray item: `instance_{action}_{id}`
+fn instance_menu_id(action: &str, id: &str) -> String {
+    format!("instance_{}_{}", action, menu_id_part(id))
+}
+
+/// Resolve a per-instance menu id back to its action and instance
+fn resolve_instance_menu_id<R: tauri::Runtime>(handle: &tauri::AppHandle<R>, id: &str) -> Option<(&'static str, BackendInstance)> {
+    let instances = handle.try_state::<SettingsState>()?.get().instances;
+    ["start", "stop", "restart"].into_iter().find_map(|action| {
+        instances.iter()
+            .find(|i| instance_menu_id(action, &i.id) == id)
+            .map(|i| (action, i.clone()))
+    })
+}
+
+fn phase_label(phase: BackendPhase) -> &'static str {
+    match phase {
+        BackendPhase::Starting => "Starting",
+        BackendPhase::Running => "Running",
+        BackendPhase::Restarting => "Restarting",
+        BackendPhase::Updating => "Updating",
+        BackendPhase::Crashed => "Crashed",
+        BackendPhase::Stopped => "Stopped",
+    }
+}
+
+// How often `await_healthy` checks the status
+const AWAIT_HEALTHY_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Wait for the gateway to come up, e.g. after a start or restart
+///
+/// Resolves with the first status where the gateway is running, or with
+/// `require_tools_ok` where additionally no enabled tool is failing (as the
+/// tray counts them, see `icon_scope`). Errors once `timeout_secs` elapse.
+#[tauri::command]
+async fn await_healthy(timeout_secs: u64, require_tools_ok: Option<bool>) -> Result<AppStatus, String> {
+    let client = http_client()?;
+    let require_tools_ok = require_tools_ok.unwrap_or(false);
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(timeout_secs);
+    let mut last = None;
+    loop {
+        if let Ok(status) = tokio::time::timeout_at(deadline, fetch_status(&client)).await {
+            if let Some(s) = &status {
+                let tools_ok = matches!(compute_health(&status).state, HealthState::Ok | HealthState::Idle);
+                if s.gateway_running && (tools_ok || !require_tools_ok) {
+                    return Ok(s.clone());
+                }
+            }
+            last = status;
+        }
+        if tokio::time::Instant::now() + AWAIT_HEALTHY_INTERVAL >= deadline {
+            break;
+        }
+        tokio::time::sleep(AWAIT_HEALTHY_INTERVAL).await;
+    }
+    Err(match last {
+        None => format!("Backend still unreachable after {}s", timeout_secs),
+        Some(s) if !s.gateway_running => format!("Gateway still not running after {}s", timeout_secs),
+        Some(_) => format!("Tools still failing after {}s", timeout_secs),
+    })
+}
+
+// Backend state files set aside by `hard_reset_backend` when asked to
+const BACKEND_STATE_FILES: &[&str] = &["settings.yaml", "profiles.yaml"];
+
+/// Kill every backend process not managed by the app, returning their pids
+///
+/// Catches backends left behind by a crashed app or started by hand. Running
+/// secondary instances are left alone; the primary is killed separately.
+fn kill_orphan_backends() -> Vec<u32> {
+    let managed = managed_backend_pids();
+    let mut sys = System::new_all();
+    sys.refresh_all();
+    sys.processes()
+        .iter()
+        .filter(|(pid, process)| {
+            process.name() == SIDECAR_NAME && !is_protected_pid(pid.as_u32()) && !managed.contains(&pid.as_u32())
+        })
+        .filter(|(_, process)| process.kill())
+        .map(|(pid, _)| pid.as_u32())
+        .collect()
+}
+
+/// Kill every unprotected process listening on a port, returning their pids
+fn free_port(port: u16) -> Vec<u32> {
+    let pids = match find_port_holders(port) {
+        Ok(pids) => pids,
+        Err(e) => {
+            eprintln!("Warning: Failed to check port {}: {}", port, e);
+            return Vec::new();
+        }
+    };
+    let mut sys = System::new_all();
+    sys.refresh_all();
+    pids.into_iter()
+        .filter(|pid| !is_protected_pid(*pid))
+        .filter(|pid| sys.process(Pid::from(*pid as usize)).is_some_and(|p| p.kill()))
+        .collect()
+}
+
+/// Move the backend state files aside so it starts from defaults
+///
+/// Files are renamed to `<name>.bak-<unix time>` rather than deleted.
+/// Returns `(original, backup)` pairs for `restore_backend_state`.
+fn back_up_backend_state() -> Result<Vec<(PathBuf, PathBuf)>, String> {
+    let dir = backend_data_dir().ok_or("Could not determine the backend data dir")?;
+    let stamp = unix_now();
+    let mut backups = Vec::new();
+    for name in BACKEND_STATE_FILES {
+        let file = dir.join(name);
+        if !file.exists() {
+            continue;
+        }
+        let backup = dir.join(format!("{}.bak-{}", name, stamp));
+        if let Err(e) = std::fs::rename(&file, &backup) {
+            restore_backend_state(&backups);
+            return Err(format!("Failed to back up {:?}: {}", file, e));
+        }
+        backups.push((file, backup));
+    }
+    Ok(backups)
+}
+
+/// Put backend state files moved aside by `back_up_backend_state` back
+fn restore_backend_state(backups: &[(PathBuf, PathBuf)]) {
+    for (original, backup) in backups {
+        if let Err(e) = std::fs::rename(backup, original) {
+            eprintln!("Error: Failed to restore {:?} from {:?}: {}", original, backup, e);
+        }
+    }
+}
+
+/// Seconds since the Unix epoch
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Kill every backend, free its ports and start a fresh one
+///
+/// Unlike a restart this doesn't ask the backend to shut down: it kills the
+/// managed backend and any orphans, then whatever still holds the control or
+/// MCP port. With `reset_state`, the backend's settings and profiles are
+/// backed up and removed first. `confirm` must be true.
+#[tauri::command]
+async fn hard_reset_backend(app: tauri::AppHandle, confirm: bool, reset_state: bool) -> Result<(), String> {
+    if !confirm {
+        return Err("Hard reset not confirmed".to_string());
+    }
+    let _lifecycle = begin_lifecycle(LifecycleState::Restarting)?;
+
+    kill_backend();
+    let orphans = kill_orphan_backends();
+    if !orphans.is_empty() {
+        println!("Killed orphan backend process(es) {:?}", orphans);
+    }
+
+    let control_port = CONTROL_PORT.load(Ordering::Relaxed);
+    let mcp_port = last_status().map(|s| s.mcp_port)
+        .unwrap_or_else(|| app.state::<SettingsState>().get().mcp_port);
+    for port in [control_port, mcp_port] {
+        let killed = free_port(port);
+        if !killed.is_empty() {
+            println!("Killed process(es) {:?} holding port {}", killed, port);
+        }
+    }
+
+    if reset_state {
+        for (_, backup) in back_up_backend_state()? {
+            println!("Backed up backend state to {:?}", backup);
+        }
+    }
+
+    // Give the OS a moment to release the ports
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    store_backend(spawn_backend()?);
+    println!("Backend process started after hard reset");
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.eval("window.location.reload()");
+    }
+    Ok(())
+}
+
+/// Bundle the desktop settings and the backend's config files into an archive
+///
+/// The archive layout is described in `config_bundle`; restore it with
+/// `import_config`.
+#[tauri::command]
+async fn export_config(settings: tauri::State<'_, SettingsState>) -> Result<Vec<u8>, String> {
+    let dir = backend_data_dir().ok_or("Could not determine the backend data dir")?;
+    let mut backend_files = Vec::new();
+    for name in BACKEND_STATE_FILES {
+        match std::fs::read(dir.join(name)) {
+            Ok(data) => backend_files.push((name.to_string(), data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("Failed to read {}: {}", name, e)),
+        }
+    }
+
+    ConfigBundle {
+        manifest: config_bundle::Manifest {
+            format: config_bundle::FORMAT_VERSION,
+            app_version: APP_VERSION.to_string(),
+            created_at: unix_now(),
+        },
+        desktop_settings: serde_json::to_vec_pretty(&settings.get()).map_err(|e| e.to_string())?,
+        backend_files,
+    }
+    .pack()
+}
+
+/// Restore an archive made by `export_config`
+///
+/// The archive is fully validated first. The backend is then stopped, its
+/// current config files are backed up (see `back_up_backend_state`) and
+/// replaced, and it is started again. If anything fails along the way the
+/// backed up files are put back.
+#[tauri::command]
+async fn import_config(app: tauri::AppHandle, bytes: Vec<u8>) -> Result<(), String> {
+    let bundle = ConfigBundle::unpack(&bytes, BACKEND_STATE_FILES)?;
+    let imported = settings::parse_settings(&bundle.desktop_settings)
+        .map_err(|e| format!("Invalid desktop settings in archive: {}", e))?;
+    imported.validate()?;
+    let dir = backend_data_dir().ok_or("Could not determine the backend data dir")?;
+
+    let _lifecycle = begin_lifecycle(LifecycleState::Restarting)?;
+    let client = http_client()?;
+    let _ = client.post(endpoint_url(|e| e.shutdown.as_str())).send().await;
+    tokio::time::sleep(Duration::from_millis(1000)).await;
+    kill_backend();
+
+    // From here on every path restarts the backend, so a failure never leaves it down
+    let import_result = back_up_backend_state().and_then(|backups| {
+        let written = bundle.backend_files.iter().try_for_each(|(name, data)| {
+            std::fs::write(dir.join(name), data).map_err(|e| format!("Failed to write {}: {}", name, e))
+        });
+        written.and_then(|_| app.state::<SettingsState>().update(|s| *s = imported)).inspect_err(|_| {
+            for name in BACKEND_STATE_FILES {
+                let _ = std::fs::remove_file(dir.join(name));
+            }
+            restore_backend_state(&backups);
+        })
+    });
+    let result = match import_result {
+        Ok(settings) => {
+            apply_settings(&app, &settings);
+            println!("Imported config exported by v{}", bundle.manifest.app_version);
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("Error: Config import failed, kept the previous config: {}", e);
+            Err(e)
+        }
+    };
+
+    // With the new config, or the old one after a failed import
+    if let Err(e) = spawn_backend().map(store_backend) {
+        eprintln!("Error: Failed to restart the backend after importing config: {}", e);
+        return Err(match result {
+            Ok(()) => e,
+            Err(import) => format!("{}; restarting the backend also failed: {}", import, e),
+        });
+    }
+    result?;
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.eval("window.location.reload()");
+    }
+    Ok(())
+}
+
+/// Kill the backend process if it's running
+fn kill_backend() {
+    let child = BACKEND_INSTANCES.lock().ok()
+        .and_then(|mut guard| guard.remove(PRIMARY_INSTANCE))
+        .and_then(|backend| backend.child);
+    if let Some(mut child) = child {
+        log_lifecycle(LifecycleEventKind::Shutdown, format!("Stopped backend (pid {})", child.id()));
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    if let Ok(mut started) = BACKEND_STARTED_AT.lock() {
+        *started = None;
+    }
+}
+
+/// If the managed backend has exited, forget it and return its exit code
+///
+/// The outer `Option` is whether it exited; the inner one is the exit code,
+/// which is absent when it was killed by a signal.
+fn reap_exited_backend() -> Option<Option<i32>> {
+    let mut guard = BACKEND_INSTANCES.lock().ok()?;
+    let exit = guard.get_mut(PRIMARY_INSTANCE)?.child.as_mut()?.try_wait().ok()??;
+    guard.remove(PRIMARY_INSTANCE);
+    Some(exit.code())
+}
+
+/// How long the managed backend has been running
+fn backend_uptime() -> Option<Duration> {
+    managed_backend_pid()?;
+    BACKEND_STARTED_AT.lock().ok().and_then(|s| s.map(|t| t.elapsed()))
+}
+
+/// Human friendly duration, e.g. "3h 12m" or "42s"
+fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    let (days, hours, mins) = (secs / 86400, (secs % 86400) / 3600, (secs % 3600) / 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, mins)
+    } else if mins > 0 {
+        format!("{}m {}s", mins, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Seconds since the managed backend was spawned, or None if it isn't running
+#[tauri::command]
+async fn gateway_uptime() -> Result<Option<u64>, String> {
+    Ok(backend_uptime().map(|d| d.as_secs()))
+}
+
+/// Tool call load, from the backend's `/api/metrics`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvocationStats {
+    /// Tool calls currently in flight
+    #[serde(default)]
+    pub in_flight: u64,
+    /// Tool calls since the backend started
+    #[serde(default)]
+    pub total: u64,
+}
+
+// Last metrics seen by the poller, for the tooltip; None if the backend has none
+static LAST_INVOCATIONS: Mutex<Option<InvocationStats>> = Mutex::new(None);
+
+// Set once the backend turned out to have no metrics endpoint; cleared when
+// a backend (re)connects, as it may be a newer one
+static METRICS_UNSUPPORTED: AtomicBool = AtomicBool::new(false);
+
+const METRICS_UNSUPPORTED_MESSAGE: &str = "This backend doesn't expose metrics";
+
+async fn fetch_invocations(client: &reqwest::Client) -> Result<InvocationStats, String> {
+    let resp = client.get(control_url(&["api", "metrics"])?)
+        .timeout(Duration::from_secs(5))
+        .send().await
+        .map_err(|e| format!("Failed to fetch metrics: {}", e))?;
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        METRICS_UNSUPPORTED.store(true, Ordering::SeqCst);
+        return Err(METRICS_UNSUPPORTED_MESSAGE.to_string());
+    }
+    if !resp.status().is_success() {
+        return Err(format!("Failed to fetch metrics: HTTP {}", resp.status()));
+    }
+    resp.json().await.map_err(|e| format!("Invalid metrics: {}", e))
+}
+
+/// Tool calls in flight and in total, if the backend exposes metrics
+#[tauri::command]
+async fn active_invocations() -> Result<InvocationStats, String> {
+    fetch_invocations(&http_client()?).await
+}
+
+/// Refresh the tool call stats for the tooltip, emitting `tool-activity`
+/// when they change
+async fn poll_tool_activity<R: tauri::Runtime>(handle: &tauri::AppHandle<R>, client: &reqwest::Client) {
+    if METRICS_UNSUPPORTED.load(Ordering::SeqCst) {
+        return;
+    }
+    let stats = fetch_invocations(client).await.ok();
+    let changed = LAST_INVOCATIONS.lock().map(|mut last| std::mem::replace(&mut *last, stats) != stats);
+    if let (Some(stats), Ok(true)) = (stats, changed) {
+        let _ = handle.emit("tool-activity", stats);
+    }
+}
+
+/// Refresh the tray tooltip and compact title (uptime changes every tick, so
+/// this runs each poll)
+fn update_tray_tooltip<R: tauri::Runtime>(handle: &tauri::AppHandle<R>) {
+    if let Some(tray) = handle.tray_by_id("main-tray") {
+        let offline_for = OFFLINE_SINCE.lock().ok().and_then(|s| s.map(|t| t.elapsed()));
+        let mut tooltip = match (offline_for, backend_uptime()) {
+            (Some(offline), _) => format!("MCP Scooter — offline for {}", format_duration(offline)),
+            (None, Some(uptime)) => format!("MCP Scooter — up {}", format_duration(uptime)),
+            (None, None) => "MCP Scooter".to_string(),
+        };
+        let invocations = LAST_INVOCATIONS.lock().ok().and_then(|s| *s).filter(|_| offline_for.is_none());
+        if let Some(stats) = invocations {
+            let calls = if stats.in_flight == 1 { "call" } else { "calls" };
+            tooltip.push_str(&format!(" — {} {} in flight", stats.in_flight, calls));
+        }
+
+        let summary = TRAY_COMPACT_TITLE.load(Ordering::Relaxed)
+            .then(|| compact_tool_summary(&last_status()))
+            .flatten();
+        // Windows trays have no title, so the counts go in the tooltip there
+        if cfg!(any(target_os = "macos", target_os = "linux")) {
+            let _ = tray.set_title(summary.as_deref());
+        } else if let Some(summary) = summary {
+            tooltip.push_str(&format!(" ({})", summary));
+        }
+        let _ = tray.set_tooltip(Some(tooltip));
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PortConflict {
+    pub port: u16,
+    pub process: ProcessInfo,
+}
+
+/// If the gateway failed to come up, check whether something else holds the
+/// MCP port and tell the UI so it can offer to free it
+async fn detect_mcp_port_conflict<R: tauri::Runtime>(handle: &tauri::AppHandle<R>, status: &AppStatus) {
+    if status.gateway_running {
+        return;
+    }
+    match check_port_usage(status.mcp_port).await {
+        Ok(Some(process)) if Some(process.pid) != managed_backend_pid() => {
+            eprintln!("Warning: MCP port {} is held by {} (pid {})", status.mcp_port, process.name, process.pid);
+            log_lifecycle(
+                LifecycleEventKind::PortConflict,
+                format!("MCP port {} is held by {} (pid {})", status.mcp_port, process.name, process.pid),
+            );
+            let _ = handle.emit("port-conflict", PortConflict { port: status.mcp_port, process });
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("Warning: Failed to check MCP port {}: {}", status.mcp_port, e),
+    }
+}
+
+// Normal delay between status polls, from the `poll_interval_secs` setting
+static POLL_INTERVAL_SECS: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(5);
+
+// Upper bound for the poll delay while the backend keeps returning 5xx
+const MAX_POLL_BACKOFF: Duration = Duration::from_secs(60);
+
+// Consecutive 5xx responses after which `backend-unhealthy` is emitted
+const UNHEALTHY_THRESHOLD: u32 = 3;
+
+/// Delay before the next poll after `errors` consecutive server errors
+fn poll_delay(errors: u32) -> Duration {
+    let interval = Duration::from_secs(POLL_INTERVAL_SECS.load(Ordering::Relaxed));
+    if errors == 0 {
+        return interval;
+    }
+    interval
+        .saturating_mul(2u32.saturating_pow(errors - 1))
+        .min(MAX_POLL_BACKOFF)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BackendExited {
+    pub code: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BackendHung {
+    pub pid: u32,
+    pub unreachable_secs: u64,
+}
+
+/// Tracks how long a live backend has failed to answer, to detect hangs
+#[derive(Default)]
+struct HangWatchdog {
+    unreachable_since: Option<std::time::Instant>,
+    reported: bool,
+}
+
+impl HangWatchdog {
+    /// Feed one poll result; returns how long the backend has been
+    /// unreachable the first time that exceeds `timeout`
+    fn observe(&mut self, reachable: bool, timeout: Duration) -> Option<Duration> {
+        if reachable {
+            *self = Self::default();
+            return None;
+        }
+        let since = *self.unreachable_since.get_or_insert_with(std::time::Instant::now);
+        if !self.reported && since.elapsed() > timeout {
+            self.reported = true;
+            return Some(since.elapsed());
+        }
+        None
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BackendMemoryExceeded {
+    pub pid: u32,
+    pub memory_mb: u64,
+    pub limit_mb: u64,
+    /// How long the backend has been over the limit
+    pub over_secs: u64,
+    /// Whether it's being restarted (see `restart_on_memory_limit`)
+    pub restarting: bool,
+}
+
+// How long the backend may stay above `max_backend_memory_mb`, so a short
+// spike (e.g. while a tool starts) isn't treated as a runaway backend
+const MEMORY_LIMIT_GRACE: Duration = Duration::from_secs(30);
+
+/// Tracks how long the backend has been over its memory limit
+struct MemoryWatchdog {
+    over_since: Option<std::time::Instant>,
+    reported: bool,
+}
+
+// Shared by the status poller and the resource monitor, which both feed it
+static MEMORY_WATCHDOG: Mutex<MemoryWatchdog> = Mutex::new(MemoryWatchdog { over_since: None, reported: false });
+
+impl MemoryWatchdog {
+    /// Feed one sample; returns how long the backend has been over the
+    /// limit the first time that exceeds `MEMORY_LIMIT_GRACE`
+    fn observe(&mut self, over: bool) -> Option<Duration> {
+        if !over {
+            self.over_since = None;
+            self.reported = false;
+            return None;
+        }
+        let since = *self.over_since.get_or_insert_with(std::time::Instant::now);
+        if !self.reported && since.elapsed() > MEMORY_LIMIT_GRACE {
+            self.reported = true;
+            return Some(since.elapsed());
+        }
+        None
+    }
+}
+
+/// Check the managed backend's memory (from an already refreshed `System`)
+/// against `max_backend_memory_mb`, reporting and optionally restarting a
+/// backend that stays above it
+fn check_backend_memory<R: tauri::Runtime>(handle: &tauri::AppHandle<R>, sys: &System) {
+    let limit_mb = MAX_BACKEND_MEMORY_MB.load(Ordering::Relaxed);
+    let Some(pid) = managed_backend_pid().filter(|_| limit_mb > 0) else {
+        return;
+    };
+    let Some(memory_mb) = sys.process(Pid::from(pid as usize)).map(|p| p.memory() / (1024 * 1024)) else {
+        return;
+    };
+    let Some(over) = MEMORY_WATCHDOG.lock().ok().and_then(|mut w| w.observe(memory_mb > limit_mb)) else {
+        return;
+    };
+
+    let restarting = handle.try_state::<SettingsState>().is_some_and(|s| s.get().restart_on_memory_limit);
+    eprintln!("Warning: Backend (pid {}) uses {} MB, over its {} MB limit for {}s", pid, memory_mb, limit_mb, over.as_secs());
+    let _ = handle.emit("backend-memory-exceeded", BackendMemoryExceeded {
+        pid,
+        memory_mb,
+        limit_mb,
+        over_secs: over.as_secs(),
+        restarting,
+    });
+    if restarting {
+        log_lifecycle(LifecycleEventKind::Restart, format!("Restarting: {} MB is over the {} MB limit", memory_mb, limit_mb));
+        let handle = handle.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = restart_backend(&handle).await {
+                eprintln!("Error: Failed to restart backend over its memory limit: {}", e);
+            }
+        });
+    }
+}
+
+/// Sample the managed backend's memory for `check_backend_memory`, when a
+/// limit is set
+fn watch_backend_memory<R: tauri::Runtime>(handle: &tauri::AppHandle<R>, sys: &mut System) {
+    let Some(pid) = managed_backend_pid().filter(|_| MAX_BACKEND_MEMORY_MB.load(Ordering::Relaxed) > 0) else {
+        return;
+    };
+    sys.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[Pid::from(pid as usize)]), true);
+    check_backend_memory(handle, sys);
+}
+
+// Set when the backend crashed and was left down; cleared by the next spawn
+static BACKEND_CRASHED: AtomicBool = AtomicBool::new(false);
+
+// Set when the backend was stopped via `stop_gateway`; cleared by the next spawn
+static BACKEND_STOPPED: AtomicBool = AtomicBool::new(false);
+
+// When recent crashes happened, for the restart limit and "attempt N"; only
+// crashes within the `restart_window_secs` setting are kept
+static CRASH_TIMES: Mutex<std::collections::VecDeque<std::time::Instant>> =
+    Mutex::new(std::collections::VecDeque::new());
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BackendCrashLoop {
+    /// Crash restarts attempted within the window
+    pub attempts: u32,
+    pub window_secs: u64,
+    pub last_exit_code: Option<i32>,
+    pub stderr_tail: String,
+}
+
+/// Record a crash and return how many happened within `window`
+fn record_crash(window: Duration) -> u32 {
+    let Ok(mut times) = CRASH_TIMES.lock() else {
+        return 1;
+    };
+    let now = std::time::Instant::now();
+    times.retain(|t| now.duration_since(*t) <= window);
+    times.push_back(now);
+    times.len() as u32
+}
+
+/// Show a desktop notification, logging instead if that fails
+fn notify<R: tauri::Runtime>(handle: &tauri::AppHandle<R>, urgency: NotificationUrgency, title: &str, body: &str) {
+    let sound_enabled = handle.try_state::<SettingsState>().map(|s| s.get().notification_sound).unwrap_or(true);
+    let mut builder = handle.notification().builder().title(title).body(body);
+    builder = match notification_sound(urgency).filter(|_| sound_enabled) {
+        Some(sound) => builder.sound(sound),
+        None => builder.silent(),
+    };
+    if let Err(e) = builder.show() {
+        eprintln!("Warning: Failed to show notification '{}': {}", title, e);
+    }
+}
+
+/// Platform sound name for a notification's urgency; low is silent
+fn notification_sound(urgency: NotificationUrgency) -> Option<&'static str> {
+    let (normal, critical) = if cfg!(target_os = "windows") {
+        ("Default", "Reminder")
+    } else if cfg!(target_os = "macos") {
+        ("default", "Sosumi")
+    } else {
+        // freedesktop sound theme names
+        ("message-new-instant", "dialog-warning")
+    };
+    match urgency {
+        NotificationUrgency::Low => None,
+        NotificationUrgency::Normal => Some(normal),
+        NotificationUrgency::Critical => Some(critical),
+    }
+}
+
+/// Restart a crashed backend if `restart_on_crash` allows it, else leave it
+/// down and offer a Start item in the tray
+///
+/// Gives up, emitting `backend-crash-loop`, once more than the
+/// `max_restart_attempts` setting crashes happen within `restart_window_secs`,
+/// so a backend that crashes on startup doesn't restart forever.
+async fn recover_from_crash<R: tauri::Runtime>(handle: &tauri::AppHandle<R>, code: Option<i32>) {
+    let settings = handle.try_state::<SettingsState>().map(|s| s.get()).unwrap_or_default();
+    let attempt = record_crash(Duration::from_secs(settings.restart_window_secs));
+
+    if settings.restart_on_crash {
+        if attempt <= settings.max_restart_attempts {
+            // Don't race a restart or update that is already replacing the backend
+            let Ok(_lifecycle) = begin_lifecycle(LifecycleState::Restarting) else {
+                return;
+            };
+            tokio::time::sleep(Duration::from_secs(attempt as u64)).await;
+            match spawn_backend() {
+                Ok(child) => {
+                    store_backend(child);
+                    println!("Backend restarted after crash (attempt {})", attempt);
+                    log_lifecycle(LifecycleEventKind::Restart, format!("Restarted after crash (attempt {})", attempt));
+                    notify(handle, NotificationUrgency::Normal, "MCP Scooter", &format!("Gateway restarted after crash (attempt {})", attempt));
+                    return;
+                }
+                Err(e) => eprintln!("Error: Failed to restart crashed backend: {}", e),
+            }
+        } else {
+            let attempts = attempt - 1;
+            eprintln!("Error: Backend crashed {} times within {}s, giving up", attempt, settings.restart_window_secs);
+            log_lifecycle(
+                LifecycleEventKind::Crash,
+                format!("Crashed {} times within {}s, not restarting", attempt, settings.restart_window_secs),
+            );
+            let _ = handle.emit("backend-crash-loop", BackendCrashLoop {
+                attempts,
+                window_secs: settings.restart_window_secs,
+                last_exit_code: code,
+                stderr_tail: backend_stderr_tail(),
+            });
+        }
+    }
+
+    BACKEND_CRASHED.store(true, Ordering::SeqCst);
+    notify(handle, NotificationUrgency::Critical, "MCP Scooter", "Gateway crashed. Use Start in the tray menu to bring it back.");
+    update_tray(handle, &last_status());
+}
+
+/// Start the backend again after it crashed and was left down
+///
+/// Also resets the crash count, so automatic restarts work again.
+fn start_crashed_backend() -> Result<(), String> {
+    if let Ok(mut times) = CRASH_TIMES.lock() {
+        times.clear();
+    }
+    store_backend(spawn_backend()?);
+    println!("Backend process started from the tray");
+    Ok(())
+}
+
+/// Report (and optionally restart) a backend whose process exited or that
+/// is alive but no longer answering
+async fn watch_backend<R: tauri::Runtime>(
+    handle: &tauri::AppHandle<R>,
+    watchdog: &mut HangWatchdog,
+    reachable: bool,
+) {
+    let started_at = BACKEND_STARTED_AT.lock().ok().and_then(|s| *s);
+    if let Some(code) = reap_exited_backend() {
+        *watchdog = HangWatchdog::default();
+        if LAUNCHED_ELEVATED.swap(false, Ordering::SeqCst) && elevation_cancelled(code) {
+            fall_back_unelevated(handle);
+            return;
         }
-        Err(e) => {
-            Err(format!("Failed to check for updates: {}", e))
+        eprintln!("Warning: Backend process exited with code {:?}", code);
+        let _ = handle.emit("backend-exited", BackendExited { code });
+        if code == Some(0) {
+            log_lifecycle(LifecycleEventKind::Shutdown, "Backend exited cleanly");
+        } else {
+            log_lifecycle(LifecycleEventKind::Crash, format!("Backend exited with code {:?}", code));
+        }
+        report_startup_failure(handle, started_at, code);
+        // A clean exit was asked for (e.g. via the shutdown endpoint); anything else is a crash
+        if code != Some(0) {
+            recover_from_crash(handle, code).await;
         }
+        return;
     }
-}
 
-/// Download and install the available update
-#[tauri::command]
-async fn install_update(app: tauri::AppHandle, include_beta: bool) -> Result<(), String> {
-    let endpoint = if include_beta {
-        "https://github.com/mcp-scooter/scooter/releases/download/updater/beta.json"
-    } else {
-        "https://github.com/mcp-scooter/scooter/releases/download/updater/latest.json"
+    // Only a process that is still alive can be hung
+    let Some(pid) = managed_backend_pid() else {
+        *watchdog = HangWatchdog::default();
+        return;
     };
-    
-    let updater = app.updater_builder()
-        .endpoints(vec![endpoint.parse().map_err(|e: url::ParseError| format!("Invalid URL: {}", e))?])
-        .map_err(|e| format!("Failed to set endpoints: {}", e))?
-        .build()
-        .map_err(|e| format!("Failed to build updater: {}", e))?;
-    
-    match updater.check().await {
-        Ok(Some(update)) => {
-            // Download and install
-            update.download_and_install(
-                |_chunk_length: usize, _content_length: Option<u64>| {},
-                || {}
-            )
-                .await
-                .map_err(|e| format!("Failed to install update: {}", e))?;
-            Ok(())
+
+    let settings = handle.try_state::<SettingsState>().map(|s| s.get()).unwrap_or_default();
+    if let Some(unreachable) = watchdog.observe(reachable, Duration::from_secs(settings.hang_timeout_secs)) {
+        eprintln!("Warning: Backend (pid {}) is alive but unreachable for {}s, treating it as hung", pid, unreachable.as_secs());
+        let _ = handle.emit("backend-hung", BackendHung { pid, unreachable_secs: unreachable.as_secs() });
+        if settings.restart_on_hang {
+            if let Err(e) = restart_backend(handle).await {
+                eprintln!("Error: Failed to restart hung backend: {}", e);
+            }
         }
-        Ok(None) => {
-            Err("No update available".to_string())
+    }
+}
+
+// Whether the event stream task is running (connected or reconnecting)
+static EVENT_STREAM_RUNNING: AtomicBool = AtomicBool::new(false);
+
+// Whether status updates are currently being pushed over the event stream
+static EVENT_STREAM_CONNECTED: AtomicBool = AtomicBool::new(false);
+
+// Wakes the poller when the event stream goes away, so polling resumes
+// right away instead of after the slow interval
+static EVENT_STREAM_DROPPED: tokio::sync::Notify = tokio::sync::Notify::const_new();
+
+// Poll interval while the event stream is connected; polling then only backs
+// up the stream and feeds the hang watchdog
+const STREAM_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+// Consecutive failed connection attempts after which the stream is given up
+const EVENT_STREAM_MAX_FAILURES: u32 = 5;
+
+/// Why the event stream ended
+enum EventStreamError {
+    /// The backend has no `/api/events` endpoint
+    Unsupported,
+    Failed(String),
+}
+
+/// Handle one server-sent event from `/api/events`
+///
+/// Only `status` events are used; `connected` and anything newer backends
+/// add are ignored.
+fn handle_backend_event<R: tauri::Runtime>(handle: &tauri::AppHandle<R>, event: &str, data: &str) {
+    if event != "status" {
+        return;
+    }
+    match serde_json::from_str::<AppStatus>(data) {
+        Ok(status) => {
+            apply_status(handle, Some(status));
+            update_tray_tooltip(handle);
         }
-        Err(e) => {
-            Err(format!("Failed to check for updates: {}", e))
+        Err(e) => eprintln!("Warning: Ignoring invalid status event: {}", e),
+    }
+}
+
+/// Read `/api/events` until it closes, applying each event as it arrives
+///
+/// Returns Ok when the backend closed a stream that had been connected.
+async fn stream_backend_events<R: tauri::Runtime>(
+    handle: &tauri::AppHandle<R>,
+    client: &reqwest::Client,
+) -> Result<(), EventStreamError> {
+    let mut resp = client.get(format!("{}/api/events", control_api()))
+        .header("Accept", "text/event-stream")
+        .send().await
+        .map_err(|e| EventStreamError::Failed(e.to_string()))?;
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(EventStreamError::Unsupported);
+    }
+    if !resp.status().is_success() {
+        return Err(EventStreamError::Failed(format!("HTTP {}", resp.status())));
+    }
+
+    EVENT_STREAM_CONNECTED.store(true, Ordering::SeqCst);
+    println!("Subscribed to backend event stream");
+
+    // Buffer raw bytes so a UTF-8 sequence split across chunks stays intact
+    let mut buffer: Vec<u8> = Vec::new();
+    while let Some(chunk) = resp.chunk().await.map_err(|e| EventStreamError::Failed(e.to_string()))? {
+        buffer.extend(chunk.iter().filter(|b| **b != b'\r'));
+        // Events are separated by a blank line
+        while let Some(end) = buffer.windows(2).position(|w| w == b"\n\n") {
+            let raw: Vec<u8> = buffer.drain(..end + 2).collect();
+            let block = String::from_utf8_lossy(&raw);
+            let mut event = "message";
+            let mut data = Vec::new();
+            for line in block.lines() {
+                if let Some(value) = line.strip_prefix("event:") {
+                    event = value.trim();
+                } else if let Some(value) = line.strip_prefix("data:") {
+                    data.push(value.trim_start());
+                }
+            }
+            handle_backend_event(handle, event, &data.join("\n"));
         }
     }
+    Ok(())
 }
 
-/// Spawn the scooter backend process
-fn spawn_backend() -> Result<Child, String> {
-    // Get the path to the sidecar binary
-    let exe_dir = std::env::current_exe()
-        .map_err(|e| format!("Failed to get current exe path: {}", e))?
-        .parent()
-        .ok_or("Failed to get exe directory")?
-        .to_path_buf();
-    
-    // The sidecar binary is in the same directory as the main executable
-    #[cfg(target_os = "windows")]
-    let sidecar_name = "scooter.exe";
-    #[cfg(not(target_os = "windows"))]
-    let sidecar_name = "scooter";
-    
-    let sidecar_path = exe_dir.join(sidecar_name);
-    
-    if !sidecar_path.exists() {
-        return Err(format!("Backend binary not found at: {:?}", sidecar_path));
+/// Subscribe to the backend's event stream, if it has one, for instant updates
+///
+/// Reconnects with backoff when the stream drops. After repeated failures, or
+/// straight away if the backend doesn't support it, the task ends and the
+/// poll loop takes over again; it calls this again on its next reconnect.
+fn start_event_stream<R: tauri::Runtime>(handle: &tauri::AppHandle<R>) {
+    if EVENT_STREAM_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
     }
-    
-    // Spawn the backend process
-    let mut cmd = Command::new(&sidecar_path);
-    cmd.current_dir(&exe_dir) // Set working directory to exe location so it finds appdata
-        .stdout(Stdio::null())
-        .stderr(Stdio::null());
-    
-    // On Windows, hide the console window
-    #[cfg(target_os = "windows")]
-    cmd.creation_flags(CREATE_NO_WINDOW);
-    
-    let child = cmd.spawn()
-        .map_err(|e| format!("Failed to spawn backend: {}", e))?;
-    
-    Ok(child)
+    let handle = handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut failures: u32 = 0;
+        loop {
+            // No overall timeout: the request lives as long as the stream.
+            // Fetched per attempt so reconnects use a client rebuilt by `reset_poller`.
+            let Ok(client) = http_client() else { break };
+            let result = stream_backend_events(&handle, &client).await;
+            let was_connected = EVENT_STREAM_CONNECTED.swap(false, Ordering::SeqCst);
+            if was_connected {
+                failures = 0;
+                EVENT_STREAM_DROPPED.notify_one();
+            }
+            match result {
+                Err(EventStreamError::Unsupported) => break,
+                Err(EventStreamError::Failed(e)) if !was_connected => {
+                    failures += 1;
+                    if failures >= EVENT_STREAM_MAX_FAILURES {
+                        eprintln!("Warning: Giving up on backend event stream, falling back to polling: {}", e);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+            tokio::time::sleep(Duration::from_secs(1 << failures)).await;
+        }
+        EVENT_STREAM_RUNNING.store(false, Ordering::SeqCst);
+    });
 }
 
-/// Kill the backend process if it's running
-fn kill_backend() {
-    if let Ok(mut guard) = BACKEND_PROCESS.lock() {
-        if let Some(mut child) = guard.take() {
-            let _ = child.kill();
-            let _ = child.wait();
+// Set once the session is ending, so closing the window really quits
+static SESSION_ENDING: AtomicBool = AtomicBool::new(false);
+
+/// Quit cleanly when the user logs out or the OS shuts down
+///
+/// macOS and Linux send SIGTERM (SIGHUP when the controlling terminal goes
+/// away), which would otherwise kill the app without stopping the backend.
+/// Windows doesn't route session end through window close events and ends
+/// every process in the session itself, so this is unix only.
+#[cfg(unix)]
+fn watch_session_end<R: tauri::Runtime>(handle: tauri::AppHandle<R>) {
+    use tokio::signal::unix::{signal, SignalKind};
+    tauri::async_runtime::spawn(async move {
+        let (Ok(mut term), Ok(mut hangup)) = (signal(SignalKind::terminate()), signal(SignalKind::hangup())) else {
+            eprintln!("Warning: Failed to listen for session end signals");
+            return;
+        };
+        tokio::select! {
+            _ = term.recv() => {}
+            _ = hangup.recv() => {}
+        }
+        println!("Session ending, shutting down");
+        SESSION_ENDING.store(true, Ordering::SeqCst);
+        log_lifecycle(LifecycleEventKind::Shutdown, "Session ending");
+        // Exit through the normal path so RunEvent::Exit stops the backend
+        handle.exit(0);
+    });
+}
+
+#[cfg(not(unix))]
+fn watch_session_end<R: tauri::Runtime>(_handle: tauri::AppHandle<R>) {}
+
+/// Show, restore and focus the main window
+fn show_main_window<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+// Wakes the poller to rebuild its client and poll right away, see `reset_poller`
+static POLLER_RESET: tokio::sync::Notify = tokio::sync::Notify::const_new();
+
+/// Reset the status poller, e.g. after changing connection settings
+///
+/// The poller drops its HTTP client (and with it any pooled connections to
+/// the old address), clears its backoff and hang timers and polls at once.
+#[tauri::command]
+async fn reset_poller() -> Result<(), String> {
+    if let Ok(mut client) = HTTP_CLIENT.lock() {
+        *client = None;
+    }
+    // Build the new client here so a failure reaches the caller
+    http_client()?;
+    POLLER_RESET.notify_one();
+    Ok(())
+}
+
+/// Poll the backend status in the background and keep the tray up to date
+fn spawn_status_poller<R: tauri::Runtime>(handle: tauri::AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        // Reported once at startup (`network-init-failed`); without a client
+        // there is nothing to poll, so the tray keeps its initial state
+        let mut client = match http_client() {
+            Ok(client) => client,
+            Err(e) => {
+                eprintln!("Error: Status polling disabled: {}", e);
+                return;
+            }
+        };
+        let mut mcp_port_checked = false;
+        let mut server_errors: u32 = 0;
+        let mut watchdog = HangWatchdog::default();
+        let mut memory_sys = System::new();
+
+        // Initial delay to let backend start
+        tokio::time::sleep(Duration::from_secs(2)).await;
+
+        loop {
+            // Only pull the full status while the window is visible (or
+            // we have nothing to show yet); otherwise a ping is enough to
+            // know whether the last status is still valid.
+            let window_visible = handle.get_webview_window("main")
+                .and_then(|w| w.is_visible().ok())
+                .unwrap_or(false);
+            let status = if window_visible || last_status().is_none() {
+                match try_fetch_status(&client).await {
+                    Ok(status) => {
+                        server_errors = 0;
+                        Some(status)
+                    }
+                    Err(StatusError::Http(code)) if code >= 500 => {
+                        // Back off instead of hammering a backend stuck in an
+                        // error loop, and report it once rather than every poll
+                        server_errors += 1;
+                        if server_errors == UNHEALTHY_THRESHOLD {
+                            eprintln!("Warning: Backend returned HTTP {} {} times in a row, backing off", code, server_errors);
+                            let _ = handle.emit("backend-unhealthy", code);
+                        }
+                        None
+                    }
+                    Err(_) => {
+                        server_errors = 0;
+                        None
+                    }
+                }
+            } else if ping_backend(&client).await {
+                last_status()
+            } else {
+                None
+            };
+
+            // (Re)connected: find out what this backend supports before the
+            // tray is rebuilt
+            if status.is_some() && last_status().is_none() {
+                METRICS_UNSUPPORTED.store(false, Ordering::SeqCst);
+                refresh_backend_version(&handle, &client).await;
+                start_event_stream(&handle);
+            }
+
+            // The first status tells us the MCP port; check it once
+            if !mcp_port_checked {
+                if let Some(s) = &status {
+                    mcp_port_checked = true;
+                    detect_mcp_port_conflict(&handle, s).await;
+                }
+            }
+
+            let reachable = status.is_some();
+            apply_status(&handle, status);
+            if reachable {
+                poll_tool_activity(&handle, &client).await;
+            }
+            update_tray_tooltip(&handle);
+            watch_backend(&handle, &mut watchdog, reachable).await;
+            watch_backend_memory(&handle, &mut memory_sys);
+            poll_instances(&handle, &client).await;
+
+            let delay = if EVENT_STREAM_CONNECTED.load(Ordering::SeqCst) {
+                STREAM_POLL_INTERVAL
+            } else {
+                poll_delay(server_errors)
+            };
+            let reset = tokio::select! {
+                _ = tokio::time::sleep(delay) => false,
+                _ = EVENT_STREAM_DROPPED.notified() => false,
+                _ = POLLER_RESET.notified() => true,
+            };
+            if reset {
+                match http_client() {
+                    Ok(new_client) => client = new_client,
+                    Err(e) => eprintln!("Error: Failed to rebuild HTTP client, keeping the old one: {}", e),
+                }
+                server_errors = 0;
+                watchdog = HangWatchdog::default();
+                mcp_port_checked = false;
+                println!("Status poller reset");
+            }
         }
+    });
+}
+
+/// Coarse state of the managed backend, as seen by the desktop app
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendPhase {
+    /// Spawned but not answering yet
+    Starting,
+    Running,
+    Restarting,
+    /// An app update is being installed
+    Updating,
+    /// Crashed and left down (see `restart_on_crash`)
+    Crashed,
+    Stopped,
+}
+
+fn backend_phase() -> BackendPhase {
+    match LIFECYCLE.lock().map(|s| *s).unwrap_or(LifecycleState::Idle) {
+        LifecycleState::Restarting => return BackendPhase::Restarting,
+        LifecycleState::InstallingUpdate => return BackendPhase::Updating,
+        LifecycleState::Idle => {}
+    }
+    if BACKEND_CRASHED.load(Ordering::SeqCst) {
+        BackendPhase::Crashed
+    } else if last_status().is_some() {
+        BackendPhase::Running
+    } else if managed_backend_pid().is_some() {
+        BackendPhase::Starting
+    } else {
+        BackendPhase::Stopped
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AgentHealth {
+    pub phase: BackendPhase,
+    pub health: HealthSummary,
+    pub backend_pid: Option<u32>,
+    pub app_version: String,
+}
+
+/// Port for the agent health endpoint: `SCOOTER_AGENT_HEALTH_PORT`, else the
+/// `agent_health_port` setting; the endpoint is off when neither is set
+fn agent_health_port(settings: &Settings) -> Option<u16> {
+    match std::env::var("SCOOTER_AGENT_HEALTH_PORT") {
+        Ok(port) if !port.trim().is_empty() => match port.trim().parse::<u16>() {
+            Ok(port) => Some(port),
+            Err(_) => {
+                eprintln!("Warning: Ignoring invalid SCOOTER_AGENT_HEALTH_PORT '{}'", port);
+                settings.agent_health_port
+            }
+        },
+        _ => settings.agent_health_port,
     }
 }
 
+/// Answer one request on the agent health endpoint
+async fn handle_agent_health_request(mut stream: tokio::net::TcpStream) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut buf = [0u8; 1024];
+    let n = match tokio::time::timeout(Duration::from_secs(5), stream.read(&mut buf)).await {
+        Ok(Ok(n)) => n,
+        _ => return,
+    };
+    let request = String::from_utf8_lossy(&buf[..n]);
+    let mut request_line = request.split_whitespace();
+    let (method, path) = (request_line.next(), request_line.next());
+
+    let (status, body) = match (method, path) {
+        (Some("GET"), Some("/health")) => {
+            let health = AgentHealth {
+                phase: backend_phase(),
+                health: compute_health(&last_status()),
+                backend_pid: managed_backend_pid(),
+                app_version: APP_VERSION.to_string(),
+            };
+            ("200 OK", serde_json::to_string(&health).unwrap_or_default())
+        }
+        (Some("GET"), _) => ("404 Not Found", r#"{"error":"not found"}"#.to_string()),
+        _ => ("405 Method Not Allowed", r#"{"error":"method not allowed"}"#.to_string()),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+}
+
+/// Serve the agent health endpoint on loopback, for external monitoring of
+/// the desktop app itself (not the backend, which has its own API)
+///
+/// `GET /health` returns an `AgentHealth` as JSON, e.g.
+/// `{"phase":"running","health":{"state":"ok","tools_enabled_count":3},
+/// "backend_pid":4242,"app_version":"0.0.1"}`.
+fn spawn_agent_health_server(port: u16) {
+    tauri::async_runtime::spawn(async move {
+        let listener = match tokio::net::TcpListener::bind(("127.0.0.1", port)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                eprintln!("Warning: Failed to start agent health endpoint on port {}: {}", port, e);
+                return;
+            }
+        };
+        println!("Agent health endpoint listening on http://127.0.0.1:{}/health", port);
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tauri::async_runtime::spawn(handle_agent_health_request(stream));
+                }
+                Err(e) => {
+                    eprintln!("Warning: Agent health endpoint failed to accept: {}", e);
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+        }
+    });
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
-        .invoke_handler(tauri::generate_handler![check_port_usage, kill_process, check_for_updates, install_update])
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_dialog::init())
+        .invoke_handler(tauri::generate_handler![
+            check_port_usage,
+            kill_process,
+            kill_port_holder,
+            check_for_updates,
+            install_update,
+            open_release_notes,
+            test_update_connectivity,
+            get_active_profile,
+            set_active_profile,
+            collect_diagnostics,
+            report_bug,
+            data_dir_status,
+            use_fallback_data_dir,
+            reset_updater_state,
+            get_health,
+            set_close_behavior,
+            resolve_close_request,
+            get_backend_log_level,
+            set_backend_log_level,
+            gateway_uptime,
+            set_tool_enabled,
+            restart_app,
+            reload_gateway_config,
+            download_update,
+            apply_staged_update,
+            list_profiles,
+            tool_config_snippet,
+            get_settings,
+            update_settings,
+            hard_reset_backend,
+            process_details,
+            export_config,
+            import_config,
+            set_always_on_top,
+            list_update_channels,
+            rescan_tools,
+            update_status,
+            select_backend_binary,
+            lifecycle_log,
+            data_dir_usage,
+            clear_backend_logs,
+            check_assets,
+            repair_data_dir_permissions,
+            restart_gateway,
+            effective_config,
+            set_event_verbosity,
+            copy_recent_logs,
+            all_scooter_resources,
+            start_resource_monitor,
+            stop_resource_monitor,
+            mute_tool,
+            unmute_tool,
+            updates_policy_disabled,
+            await_healthy,
+            list_instances,
+            start_gateway,
+            stop_gateway,
+            tool_error_detail,
+            export_all_client_configs,
+            validate_profile,
+            snapshot_state,
+            compare_snapshots,
+            update_history,
+            test_shutdown_endpoint,
+            version_info,
+            reset_poller,
+            check_clock_skew,
+            test_proxy,
+            settings_schema_version,
+            active_invocations,
+        ])
         .setup(|app| {
             let handle = app.handle().clone();
+            // The main window isn't created from the config, so portable mode
+            // can point its webview data at the portable dir
+            if let Some(config) = app.config().app.windows.iter().find(|w| w.label == "main").cloned() {
+                portable::configure_window(tauri::WebviewWindowBuilder::from_config(&handle, &config)?).build()?;
+            }
+            let _ = DEBUG_EVENT_HANDLE.set(handle.clone());
+            let settings = SettingsState::load(&handle);
+            apply_settings(&handle, &settings.get());
+            app.manage(settings);
+            // After the settings, which hold the proxies the client is built with
+            if let Err(message) = http_client() {
+                eprintln!("Error: {}", message);
+                let _ = handle.emit("network-init-failed", NetworkInitFailed { message });
+            }
+            if let Ok(dir) = handle.path().resource_dir() {
+                let _ = RESOURCE_DIR.set(dir);
+            }
+            let history_dir = portable::dir().map(PathBuf::from).or_else(|| handle.path().app_local_data_dir().ok());
+            if let Some(dir) = history_dir {
+                let _ = UPDATE_HISTORY_PATH.set(dir.join(UPDATE_HISTORY_FILE));
+            }
+            let updated_from = detect_updated_from();
+            if let Some(previous) = &updated_from {
+                log_lifecycle(LifecycleEventKind::UpdateInstall, format!("Relaunched after updating from {}", previous));
+            }
+            let _ = UPDATED_FROM.set(updated_from);
             
-            // Spawn the backend process
-            match spawn_backend() {
-                Ok(child) => {
-                    if let Ok(mut guard) = BACKEND_PROCESS.lock() {
-                        *guard = Some(child);
+            // A read-only data dir would make the backend fail right away, so
+            // don't spawn it and let the UI offer the per-user fallback instead
+            let mut data_dir = check_data_dir(&handle);
+            if !data_dir.writable {
+                // Often just a dir left behind by another account or the installer
+                if let Some(dir) = backend_data_dir() {
+                    let report = repair_data_dir(&dir);
+                    if report.writable {
+                        println!("Repaired backend data directory permissions");
+                        data_dir = check_data_dir(&handle);
                     }
-                    println!("Backend process started successfully");
                 }
-                Err(e) => {
-                    eprintln!("Warning: Failed to start backend: {}", e);
-                    // Continue anyway - the backend might already be running
+            }
+            // A hand-edited settings file isn't validated on load; a backend
+            // started with e.g. colliding ports would fail in confusing ways
+            let config_error = handle.state::<SettingsState>().get().validate().err();
+            if let Some(message) = &config_error {
+                eprintln!("Error: Invalid settings, not starting the backend: {}", message);
+                let _ = handle.emit("config-invalid", ConfigInvalid { message: message.clone() });
+            } else if !data_dir.writable {
+                eprintln!("Error: Backend data directory is not writable: {}", data_dir.error.clone().unwrap_or_default());
+                let _ = handle.emit("data-dir-readonly", &data_dir);
+            } else {
+                check_privileged_ports(&handle, &handle.state::<SettingsState>().get());
+                // Spawn the backend process
+                match spawn_backend() {
+                    Ok(child) => {
+                        store_backend(child);
+                        println!("Backend process started successfully");
+                    }
+                    Err(e) => {
+                        eprintln!("Warning: Failed to start backend: {}", e);
+                        // Continue anyway - the backend might already be running
+                    }
                 }
+                spawn_instances(&handle.state::<SettingsState>().get());
             }
             
             // Show the main window on startup
             if let Some(window) = app.get_webview_window("main") {
+                if handle.state::<SettingsState>().get().always_on_top {
+                    let _ = window.set_always_on_top(true);
+                }
                 let _ = window.show();
                 let _ = window.set_focus();
             }
             
-            // Initial menu
-            let menu = build_tray_menu(&handle, &None)?;
+            // Initial menu, from the last run's status if we have one
+            let cached_status = load_cached_status(&handle);
+            TRAY_STALE.store(cached_status.is_some(), Ordering::SeqCst);
+            let menu = build_tray_menu(&handle, &cached_status, cached_status.is_some())?;
+
+            // Stripped or CI builds may lack icons; degrade instead of panicking
+            for asset in missing_assets(&handle) {
+                eprintln!("Warning: Missing asset: {}", asset);
+            }
+            let tray_icon = app.default_window_icon().cloned().unwrap_or_else(placeholder_icon);
 
+            let left_click = handle.state::<SettingsState>().get().effective_left_click();
             let _tray = TrayIconBuilder::with_id("main-tray")
-                .icon(app.default_window_icon().unwrap().clone())
+                .icon(tray_icon)
                 .menu(&menu)
-                .show_menu_on_left_click(true)
+                .show_menu_on_left_click(left_click == TrayLeftClick::Menu)
                 .on_menu_event(move |app, event| {
                     match event.id.as_ref() {
+                        "reload" => {
+                            let handle = app.clone();
+                            tauri::async_runtime::spawn(async move {
+                                if let Err(e) = reload_gateway_config(handle).await {
+                                    eprintln!("Error: Failed to reload gateway config: {}", e);
+                                }
+                            });
+                        }
+                        "rescan_tools" => {
+                            let handle = app.clone();
+                            tauri::async_runtime::spawn(async move {
+                                if let Err(e) = rescan_tools(handle).await {
+                                    eprintln!("Error: Failed to rescan tools: {}", e);
+                                }
+                            });
+                        }
+                        "recent_logs" => {
+                            let handle = app.clone();
+                            tauri::async_runtime::spawn(async move {
+                                if let Err(e) = copy_recent_logs(handle, TRAY_COPIED_LOG_LINES).await {
+                                    eprintln!("Error: Failed to copy recent logs: {}", e);
+                                }
+                            });
+                        }
+                        "report_bug" => {
+                            let handle = app.clone();
+                            tauri::async_runtime::spawn(async move {
+                                if let Err(e) = report_bug(handle).await {
+                                    eprintln!("Error: Failed to open bug report: {}", e);
+                                }
+                            });
+                        }
                         "quit" => {
                             app.exit(0);
                         }
                         "show" => {
-                            if let Some(window) = app.get_webview_window("main") {
-                                let _ = window.show();
-                                let _ = window.set_focus();
+                            show_main_window(app);
+                        }
+                        "start_backend" => {
+                            if let Err(e) = start_crashed_backend() {
+                                eprintln!("Error: Failed to start backend: {}", e);
+                            }
+                            update_tray(app, &last_status());
+                        }
+                        "always_on_top" => {
+                            let handle = app.clone();
+                            let enabled = !handle.state::<SettingsState>().get().always_on_top;
+                            tauri::async_runtime::spawn(async move {
+                                if let Err(e) = set_always_on_top(handle.clone(), enabled).await {
+                                    eprintln!("Error: Failed to toggle always on top: {}", e);
+                                    update_tray(&handle, &last_status());
+                                }
+                            });
+                        }
+                        "restart_on_crash" => {
+                            if let Some(settings) = app.try_state::<SettingsState>() {
+                                if let Err(e) = settings.update(|s| s.restart_on_crash = !s.restart_on_crash) {
+                                    eprintln!("Error: Failed to save restart on crash setting: {}", e);
+                                }
                             }
+                            // Sync the checkmark with the saved value
+                            update_tray(app, &last_status());
                         }
                         "restart" => {
                             let handle = app.clone();
                             tauri::async_runtime::spawn(async move {
-                                // 1. Tell the backend to shutdown
-                                let client = reqwest::Client::new();
-                                let _ = client.post("http://127.0.0.1:6200/api/shutdown").send().await;
-                                
-                                // 2. Wait a bit for it to exit
-                                tokio::time::sleep(Duration::from_millis(1000)).await;
-                                
-                                // 3. Kill it just in case it's still hanging
-                                kill_backend();
-                                
-                                // 4. Spawn a new one
-                                match spawn_backend() {
-                                    Ok(child) => {
-                                        if let Ok(mut guard) = BACKEND_PROCESS.lock() {
-                                            *guard = Some(child);
-                                        }
-                                        println!("Backend process restarted successfully");
-                                    }
-                                    Err(e) => {
-                                        eprintln!("Error: Failed to restart backend: {}", e);
-                                    }
+                                if let Err(e) = restart_backend(&handle).await {
+                                    eprintln!("Error: Failed to restart backend: {}", e);
                                 }
-
-                                // 5. Reload the frontend window if it exists
-                                if let Some(window) = handle.get_webview_window("main") {
-                                    let _ = window.eval("window.location.reload()");
+                            });
+                        }
+                        id if id.starts_with("instance_") => {
+                            if let Some((action, instance)) = resolve_instance_menu_id(app, id) {
+                                let handle = app.clone();
+                                tauri::async_runtime::spawn(async move {
+                                    let id = Some(instance.id.clone());
+                                    let result = match action {
+                                        "start" => start_gateway(handle.clone(), id).await,
+                                        "stop" => stop_gateway(handle.clone(), id).await,
+                                        _ => restart_gateway(handle.clone(), None, id).await.map(|_| ()),
+                                    };
+                                    if let Err(e) = result {
+                                        eprintln!("Error: Failed to {} gateway {}: {}", action, instance.id, e);
+                                    }
+                                });
+                            }
+                        }
+                        "claude_config" => {
+                            let handle = app.clone();
+                            tauri::async_runtime::spawn(async move {
+                                let result = export_all_client_configs("claude".to_string()).await
+                                    .and_then(|config| handle.clipboard().write_text(config).map_err(|e| e.to_string()));
+                                if let Err(e) = result {
+                                    eprintln!("Error: Failed to copy Claude config: {}", e);
+                                    notify(&handle, NotificationUrgency::Normal, "MCP Scooter", &format!("Couldn't copy the Claude config: {}", e));
                                 }
                             });
                         }
+                        id if id.starts_with("error_") => {
+                            if let Some((profile, tool)) = resolve_tool_menu_id("error_", id).map(|(p, t)| (p, t.name)) {
+                                let handle = app.clone();
+                                tauri::async_runtime::spawn(async move {
+                                    let body = match tool_error_detail(profile, tool.clone()).await {
+                                        Ok(ToolError { message: Some(message), restart_count, .. }) => {
+                                            format!("{} (restarted {} times)", message, restart_count)
+                                        }
+                                        Ok(_) => "No error recorded; it may have recovered".to_string(),
+                                        Err(e) => format!("Couldn't get the error: {}", e),
+                                    };
+                                    notify(&handle, NotificationUrgency::Normal, &format!("{} is down", tool), &body);
+                                });
+                            }
+                        }
+                        id if id.starts_with("copy_") => {
+                            if let Some((profile, tool)) = resolve_tool_menu_id("copy_", id) {
+                                let handle = app.clone();
+                                tauri::async_runtime::spawn(async move {
+                                    let result = tool_config_snippet(profile, tool.name.clone()).await
+                                        .and_then(|snippet| handle.clipboard().write_text(snippet).map_err(|e| e.to_string()));
+                                    if let Err(e) = result {
+                                        eprintln!("Error: Failed to copy config for {}: {}", tool.name, e);
+                                    }
+                                });
+                            }
+                        }
+                        id if id.starts_with("mute_") => {
+                            if let Some((_, tool)) = resolve_tool_menu_id("mute_", id) {
+                                let muted = app.try_state::<SettingsState>()
+                                    .is_some_and(|s| s.get().muted_tools.contains(&tool.name));
+                                if let Err(e) = set_tool_muted(app, &tool.name, !muted) {
+                                    eprintln!("Error: Failed to save notification setting for {}: {}", tool.name, e);
+                                }
+                            }
+                            // Sync the checkmark with the saved value
+                            update_tray(app, &last_status());
+                        }
+                        id if id.starts_with("toggle_") => {
+                            if let Some((profile, tool)) = resolve_tool_menu_id("toggle_", id) {
+                                let handle = app.clone();
+                                tauri::async_runtime::spawn(async move {
+                                    let enabled = !tool.enabled;
+                                    if let Err(e) = set_tool_enabled(handle.clone(), profile.clone(), tool.name.clone(), enabled).await {
+                                        eprintln!("Error: Failed to toggle tool {} in {}: {}", tool.name, profile, e);
+                                        // Put the checkmark back to the real state
+                                        update_tray(&handle, &last_status());
+                                    }
+                                });
+                            }
+                        }
+                        id if id.starts_with("tool_") => {
+                            if let Some((profile, tool)) = resolve_tool_menu_id("tool_", id).map(|(p, t)| (p, t.name)) {
+                                let handle = app.clone();
+                                tauri::async_runtime::spawn(async move {
+                                    if let Err(e) = restart_tool(&profile, &tool).await {
+                                        eprintln!("Error: Failed to restart tool {} in {}: {}", tool, profile, e);
+                                    }
+                                    refresh_status(&handle).await;
+                                });
+                            }
+                        }
                         _ => {}
                     }
                 })
                 .on_tray_icon_event(|tray, event| {
+                    let app = tray.app_handle();
                     match event {
                         TrayIconEvent::DoubleClick { .. } => {
-                            let app = tray.app_handle();
-                            if let Some(window) = app.get_webview_window("main") {
-                                let _ = window.show();
-                                let _ = window.set_focus();
+                            show_main_window(app);
+                        }
+                        TrayIconEvent::Click {
+                            button: MouseButton::Left,
+                            button_state: MouseButtonState::Up,
+                            ..
+                        } => {
+                            let left_click = app.try_state::<SettingsState>()
+                                .map(|s| s.get().effective_left_click())
+                                .unwrap_or_default();
+                            if left_click == TrayLeftClick::Window {
+                                show_main_window(app);
                             }
                         }
                         _ => {}
@@ -409,117 +6497,216 @@ pub fn run() {
                 })
                 .build(app)?;
 
-            // Background polling for status
-            tauri::async_runtime::spawn(async move {
-                let client = reqwest::Client::new();
-                let mut last_status: Option<AppStatus> = None;
-
-                // Initial delay to let backend start
-                tokio::time::sleep(Duration::from_secs(2)).await;
-
-                loop {
-                    let status = match client.get("http://127.0.0.1:6200/api/status").send().await {
-                        Ok(resp) => {
-                            if resp.status().is_success() {
-                                match resp.text().await {
-                                    Ok(text) => {
-                                        match serde_json::from_str::<AppStatus>(&text) {
-                                            Ok(parsed) => Some(parsed),
-                                            Err(_) => None
-                                        }
-                                    },
-                                    Err(_) => None
-                                }
-                            } else {
-                                None
-                            }
-                        },
-                        Err(_) => None
-                    };
-
-                    // Check if status changed (simple check)
-                    let status_changed = match (&status, &last_status) {
-                        (Some(s), Some(ls)) => {
-                            s.gateway_running != ls.gateway_running || 
-                            s.active_profile_id != ls.active_profile_id ||
-                            s.profiles.len() != ls.profiles.len() ||
-                            s.profiles.iter().any(|p| {
-                                ls.profiles.iter().find(|lp| lp.id == p.id)
-                                    .map(|lp| lp.active_tools != p.active_tools || lp.tool_status != p.tool_status)
-                                    .unwrap_or(true)
-                            })
-                        },
-                        (None, None) => false,
-                        _ => true,
-                    };
-
-                    if status_changed {
-                        last_status = status.clone();
-                        
-                        // Update tray
-                        if let Some(tray) = handle.tray_by_id("main-tray") {
-                            // Update menu
-                            if let Ok(new_menu) = build_tray_menu(&handle, &status) {
-                                let _ = tray.set_menu(Some(new_menu));
-                            }
-
-                            // Update icon based on status
-                            let icon_name = if let Some(s) = &status {
-                                if !s.gateway_running {
-                                    "tray-error.png"
-                                } else if s.profiles.iter().any(|p| {
-                                    p.tool_status.as_deref().unwrap_or_default().iter().any(|ts| ts.status != "ok")
-                                }) {
-                                    "tray-warning.png"
-                                } else {
-                                    "tray-ok.png"
-                                }
-                            } else {
-                                "tray-error.png"
-                            };
-
-                            // Load icon based on status
-                            let icon_path = std::path::Path::new("icons").join(icon_name);
-                            let dev_icon_path = std::path::Path::new("desktop/src-tauri/icons").join(icon_name);
-                            let public_icon_path = std::path::Path::new("desktop/public/logo/icon-source.svg");
-
-                            let final_path = if icon_path.exists() {
-                                Some(icon_path)
-                            } else if dev_icon_path.exists() {
-                                Some(dev_icon_path)
-                            } else if public_icon_path.exists() && icon_name == "tray-ok.png" {
-                                Some(public_icon_path.to_path_buf())
-                            } else {
-                                None
-                            };
-
-                            if let Some(path) = final_path {
-                                if let Ok(img) = tauri::image::Image::from_path(path) {
-                                    let _ = tray.set_icon(Some(img));
-                                }
-                            }
-                        }
-                    }
-
-                    tokio::time::sleep(Duration::from_secs(5)).await;
-                }
+            // Background polling for status (once per process)
+            start_background_tasks_once(|| {
+                spawn_status_poller(handle.clone());
+                watch_session_end(handle.clone());
             });
 
+            if let Some(port) = agent_health_port(&handle.state::<SettingsState>().get()) {
+                spawn_agent_health_server(port);
+            }
+
             Ok(())
         })
         .on_window_event(|window, event| {
             if let tauri::WindowEvent::CloseRequested { api, .. } = event {
-                // Instead of closing, we just hide the window
-                window.hide().unwrap();
-                api.prevent_close();
+                // Hiding (or asking) would hold up a logout or shutdown
+                if SESSION_ENDING.load(Ordering::SeqCst) {
+                    return;
+                }
+                let behavior = window.try_state::<SettingsState>()
+                    .map(|s| s.get().close_behavior)
+                    .unwrap_or_default();
+                match behavior {
+                    CloseBehavior::Hide => {
+                        // Instead of closing, we just hide the window
+                        window.hide().unwrap();
+                        api.prevent_close();
+                        show_close_hint_once(window.app_handle());
+                    }
+                    CloseBehavior::Quit => {
+                        // Exit through the normal path so RunEvent::Exit stops the backend
+                        api.prevent_close();
+                        window.app_handle().exit(0);
+                    }
+                    CloseBehavior::Ask => {
+                        api.prevent_close();
+                        prompt_close_behavior(window.app_handle());
+                    }
+                }
             }
         })
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
-        .run(|_app_handle, event| {
-            if let tauri::RunEvent::Exit = event {
-                // Clean up the backend process when the app exits
-                kill_backend();
-            }
+        .run(|_app_handle, event| match event {
+            // Nothing prevents exiting, so stop the backends right away: the
+            // OS may not wait for RunEvent::Exit when the session is ending
+            tauri::RunEvent::ExitRequested { .. } => kill_all_backends(),
+            // Clean up the backend processes when the app exits
+            tauri::RunEvent::Exit => kill_all_backends(),
+            _ => {}
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_change_uses_semver_order() {
+        assert_eq!(version_change("0.0.9", "0.0.10"), Ok(VersionChange::Upgrade));
+        assert_eq!(version_change("v1.2.3", "1.2.3"), Ok(VersionChange::UpToDate));
+        assert_eq!(version_change("1.0.0", "1.0.0-beta.2"), Ok(VersionChange::Downgrade));
+        assert_eq!(version_change("1.0.0-beta.1", "1.0.0-beta.2"), Ok(VersionChange::Upgrade));
+        assert!(version_change("1.0.0", "latest").is_err());
+        assert!(version_change("", "1.0.0").is_err());
+    }
+
+    #[test]
+    fn versions_compatible_by_major_and_zero_minor() {
+        assert!(versions_compatible("1.2.3", "v1.9.0"));
+        assert!(!versions_compatible("1.2.3", "2.0.0"));
+        assert!(versions_compatible("0.3.1", "0.3.7-beta.1"));
+        assert!(!versions_compatible("0.3.1", "0.4.0"));
+        // Unparseable versions don't count as a mismatch
+        assert!(versions_compatible("1.0.0", "dev"));
+    }
+
+    #[test]
+    fn parse_http_date_formats() {
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), Some(784_111_777));
+        assert_eq!(parse_http_date("Thu, 01 Jan 1970 00:00:00 GMT"), Some(0));
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 UTC"), None);
+        assert_eq!(parse_http_date("Sun, 06 Foo 1994 08:49:37 GMT"), None);
+        assert_eq!(parse_http_date("Sun, 31 Feb 1994 08:49:37 GMT"), None);
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49 GMT"), None);
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 25:49:37 GMT"), None);
+        assert_eq!(parse_http_date(""), None);
+    }
+
+    #[test]
+    fn split_command_line_quotes() {
+        assert_eq!(
+            split_command_line("go run ./cmd/scooter"),
+            Ok(vec!["go".into(), "run".into(), "./cmd/scooter".into()])
+        );
+        assert_eq!(
+            split_command_line(r#"  firejail --name="my box" 'a b'c  "" "#),
+            Ok(vec!["firejail".into(), "--name=my box".into(), "a bc".into(), "".into()])
+        );
+        assert_eq!(split_command_line("   "), Ok(Vec::new()));
+        assert!(split_command_line("sh -c 'echo").is_err());
+    }
+
+    #[test]
+    fn icon_hysteresis_delays_worse_states() {
+        let mut icon = IconHysteresis { shown: None, pending: None, pending_polls: 0 };
+        assert_eq!(icon.observe(HealthState::Ok), HealthState::Ok);
+        assert_eq!(icon.observe(HealthState::Error), HealthState::Ok);
+        assert_eq!(icon.observe(HealthState::Error), HealthState::Error);
+        // Better states show right away
+        assert_eq!(icon.observe(HealthState::Warning), HealthState::Warning);
+        assert_eq!(icon.observe(HealthState::Ok), HealthState::Ok);
+        // A flapping state never reaches the icon
+        for _ in 0..3 {
+            assert_eq!(icon.observe(HealthState::Warning), HealthState::Ok);
+            assert_eq!(icon.observe(HealthState::Ok), HealthState::Ok);
+        }
+        // A different worse state restarts the count
+        assert_eq!(icon.observe(HealthState::Warning), HealthState::Ok);
+        assert_eq!(icon.observe(HealthState::Error), HealthState::Ok);
+        assert_eq!(icon.observe(HealthState::Error), HealthState::Error);
+    }
+
+    #[test]
+    fn icon_hysteresis_shows_first_state() {
+        let mut icon = IconHysteresis { shown: None, pending: None, pending_polls: 0 };
+        assert_eq!(icon.observe(HealthState::Error), HealthState::Error);
+    }
+
+    #[test]
+    fn background_tasks_start_once() {
+        let spawned = std::sync::atomic::AtomicU32::new(0);
+        let spawn = || {
+            spawned.fetch_add(1, Ordering::SeqCst);
+        };
+        assert!(start_background_tasks_once(spawn));
+        assert!(!start_background_tasks_once(spawn));
+        assert_eq!(spawned.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn update_check_while_busy_returns_current_state() {
+        let (locked, wait_locked) = std::sync::mpsc::channel();
+        let (release, wait_release) = std::sync::mpsc::channel::<()>();
+        let holder = std::thread::spawn(move || {
+            let _updater = UPDATER_LOCK.blocking_lock();
+            locked.send(()).unwrap();
+            let _ = wait_release.recv();
         });
+        wait_locked.recv().unwrap();
+        set_update_state(UpdateLifecycle::Downloaded { version: "9.9.9".to_string() });
+
+        let started = std::time::Instant::now();
+        let busy = begin_update_check().err().expect("check ran while the updater was busy");
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert!(busy.available);
+        assert_eq!(busy.version.as_deref(), Some("9.9.9"));
+        assert_eq!(busy.version_change, None);
+
+        release.send(()).unwrap();
+        holder.join().unwrap();
+        assert!(begin_update_check().is_ok());
+        set_update_state(UpdateLifecycle::UpToDate);
+    }
+
+    // kill_backend and store_backend kill and reap outside the lock, so pid
+    // lookups (backend_alive, the poller) never wait on a dying child
+    #[cfg(unix)]
+    #[test]
+    fn backend_lock_survives_concurrent_restarts() {
+        fn sleeper() -> Child {
+            Command::new("sleep").arg("30").spawn().expect("failed to spawn sleep")
+        }
+
+        store_backend(sleeper());
+        let (done, finished) = std::sync::mpsc::channel();
+        let threads: Vec<_> = (0..6)
+            .map(|i| {
+                let done = done.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..10 {
+                        if i % 3 == 0 {
+                            kill_backend();
+                            store_backend(sleeper());
+                        } else {
+                            let _ = managed_backend_pid();
+                        }
+                    }
+                    let _ = done.send(());
+                })
+            })
+            .collect();
+        drop(done);
+        for _ in 0..threads.len() {
+            finished.recv_timeout(Duration::from_secs(10)).expect("backend lock deadlocked");
+        }
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        kill_backend();
+        assert_eq!(managed_backend_pid(), None);
+    }
+
+    #[test]
+    fn poll_delay_backs_off() {
+        let interval = Duration::from_secs(POLL_INTERVAL_SECS.load(Ordering::Relaxed));
+        assert_eq!(poll_delay(0), interval);
+        assert_eq!(poll_delay(1), interval);
+        assert_eq!(poll_delay(2), (interval * 2).min(MAX_POLL_BACKOFF));
+        assert_eq!(poll_delay(3), (interval * 4).min(MAX_POLL_BACKOFF));
+        assert_eq!(poll_delay(40), MAX_POLL_BACKOFF);
+        assert_eq!(poll_delay(u32::MAX), MAX_POLL_BACKOFF);
+    }
 }
@@ -0,0 +1,56 @@
+//! Portable mode, for running from a USB stick
+//!
+//! Enabled by passing `--portable` or by placing a `portable.txt` beside the
+//! executable. Settings, caches and the backend's data (including its logs)
+//! then live in a `data` directory beside the executable instead of the OS
+//! app-data locations.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+const MARKER_FILE: &str = "portable.txt";
+const DATA_DIR: &str = "data";
+
+static PORTABLE_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+fn detect() -> Option<PathBuf> {
+    let exe_dir = crate::current_exe_dir()?;
+    let requested = std::env::args().skip(1).any(|arg| arg == "--portable");
+    if !requested && !exe_dir.join(MARKER_FILE).is_file() {
+        return None;
+    }
+    Some(exe_dir.join(DATA_DIR))
+}
+
+/// Root of the portable data dir, or None when not running portable
+pub fn dir() -> Option<&'static Path> {
+    PORTABLE_DIR.get_or_init(detect).as_deref()
+}
+
+/// Where caches (e.g. the last known status) go
+pub fn cache_dir() -> Option<PathBuf> {
+    dir().map(|d| d.join("cache"))
+}
+
+/// The backend's data dir, passed to it as `SCOOTER_CONFIG_DIR`
+pub fn backend_dir() -> Option<PathBuf> {
+    dir().map(|d| d.join("backend"))
+}
+
+/// Keep webview data inside the portable dir too
+///
+/// WebView2 and WebKitGTK keep their data and caches in the given directory.
+/// WKWebView on macOS can only choose between data stores in the OS location,
+/// so there the webview gets no persistent storage at all; the UI keeps
+/// nothing in it.
+pub fn configure_window<'a, R: tauri::Runtime, M: tauri::Manager<R>>(
+    builder: tauri::WebviewWindowBuilder<'a, R, M>,
+) -> tauri::WebviewWindowBuilder<'a, R, M> {
+    match dir() {
+        #[cfg(target_os = "macos")]
+        Some(_) => builder.incognito(true),
+        #[cfg(not(target_os = "macos"))]
+        Some(dir) => builder.data_directory(dir.join("webview")),
+        None => builder,
+    }
+}
@@ -0,0 +1,676 @@
+//! Desktop app settings, persisted as JSON in the app config dir (or the
+//! portable data dir)
+//!
+//! The file carries a schema `version` so files written by older builds can
+//! be migrated on load (see `migrate`). Saves write a temp file and rename it
+//! over the real one, so a crash mid-write never leaves a truncated file.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::Manager;
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Current schema version of the settings file
+pub const SETTINGS_VERSION: u32 = 1;
+
+/// What closing the main window does
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CloseBehavior {
+    /// Hide the window and keep running in the tray
+    #[default]
+    Hide,
+    /// Quit the app (and stop the backend)
+    Quit,
+    /// Ask the user each time, with the option to remember the answer
+    Ask,
+}
+
+/// What a left click on the tray icon does
+///
+/// Right click always opens the menu and double click always shows the window.
+/// Linux trays don't report clicks, so there the menu always opens.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayLeftClick {
+    /// Open the tray menu
+    Menu,
+    /// Show and focus the main window
+    Window,
+    /// Do nothing
+    None,
+}
+
+impl Default for TrayLeftClick {
+    /// Windows users expect left click to open the app, macOS and Linux
+    /// users expect the menu
+    fn default() -> Self {
+        if cfg!(target_os = "windows") {
+            Self::Window
+        } else {
+            Self::Menu
+        }
+    }
+}
+
+/// Which tray icon click opens the main window
+///
+/// Double click opens it either way; `Single` also makes a single left click
+/// open it, which takes precedence over `TrayLeftClick`. Where the tray
+/// reports no clicks (Linux), the menu's Open item is always available.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayOpenClick {
+    Single,
+    #[default]
+    Double,
+}
+
+/// Which of the app's environment variables the backend inherits
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvInheritance {
+    /// The app's whole environment
+    #[default]
+    Full,
+    /// PATH, the home and temp dirs and `SCOOTER_*` variables
+    Minimal,
+    /// Only what the backend needs to start and find its data dir
+    None,
+}
+
+/// How the tray menu lists a profile's tools
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayToolLayout {
+    /// Every tool directly under its profile
+    #[default]
+    Flat,
+    /// Tools in "Failing", "Warnings" and "Healthy" submenus, problems first
+    Grouped,
+}
+
+/// How loudly a notification announces itself
+///
+/// The notification plugin has no urgency hint on desktop, so this picks
+/// the sound: none for low, the default sound for normal and an alert sound
+/// for critical (e.g. the gateway crashed).
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationUrgency {
+    Low,
+    #[default]
+    Normal,
+    Critical,
+}
+
+/// Which tools count towards the tray icon and the compact tray title
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum IconScope {
+    /// Enabled tools of every running profile
+    #[default]
+    Running,
+    /// Only the active profile's enabled tools
+    ActiveProfile,
+}
+
+/// Paths of the backend control API endpoints the app relies on
+///
+/// Backend forks and versions differ here (e.g. `/shutdown` or
+/// `/api/v2/shutdown`), so they can be changed without a rebuild.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct BackendEndpoints {
+    pub status: String,
+    pub ping: String,
+    pub shutdown: String,
+}
+
+impl Default for BackendEndpoints {
+    fn default() -> Self {
+        Self {
+            status: "/api/status".to_string(),
+            ping: "/api/ping".to_string(),
+            shutdown: "/api/shutdown".to_string(),
+        }
+    }
+}
+
+/// Id of the backend started from the main settings
+pub const PRIMARY_INSTANCE: &str = "primary";
+
+/// A further backend run beside the primary one, e.g. a separate gateway for
+/// work profiles
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BackendInstance {
+    pub id: String,
+    /// Shown in the tray instead of the id
+    #[serde(default)]
+    pub name: Option<String>,
+    /// The instance's own backend data dir. The backend reads its ports from
+    /// settings.yaml in there; an instance whose `control_port` and
+    /// `mcp_port` don't match it isn't started.
+    pub data_dir: String,
+    pub control_port: u16,
+    pub mcp_port: u16,
+}
+
+impl BackendInstance {
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().filter(|n| !n.trim().is_empty()).unwrap_or(&self.id)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Settings {
+    /// Schema version, see `SETTINGS_VERSION`
+    pub version: u32,
+    pub close_behavior: CloseBehavior,
+    /// Whether the "still running in the tray" hint was shown on a first close
+    pub close_hint_shown: bool,
+    pub tray_left_click: TrayLeftClick,
+    pub tray_open_click: TrayOpenClick,
+    /// Keep the main window above other windows
+    pub always_on_top: bool,
+    pub icon_scope: IconScope,
+    /// Show ok/warning/error tool counts (e.g. "4✓ 1!") as the tray title
+    /// where trays have titles (macOS, Linux), else in the tooltip
+    pub tray_compact_title: bool,
+    pub tray_tool_layout: TrayToolLayout,
+    /// Port the backend control API is expected on (the backend reads its own
+    /// `control_port` from settings.yaml, so keep the two in sync)
+    pub control_port: u16,
+    /// Port the MCP gateway is expected on, likewise mirroring the backend's
+    pub mcp_port: u16,
+    pub endpoints: BackendEndpoints,
+    /// Delay between status polls
+    pub poll_interval_secs: u64,
+    /// How long the tray waits for further status changes before rebuilding
+    /// its menu; 0 rebuilds on every change
+    pub tray_debounce_ms: u64,
+    /// Backend data dir, overriding the backend's default location
+    pub data_dir: Option<String>,
+    /// Linux only: command line to run the backend under, e.g. `firejail`,
+    /// `bwrap ...` or `systemd-run --user --scope`
+    pub sandbox_command: Option<String>,
+    /// How much of the app's environment the backend sees, so it behaves the
+    /// same however the app was launched
+    pub env_inheritance: EnvInheritance,
+    /// Start the backend through the OS elevation prompt (UAC, pkexec or the
+    /// macOS admin password dialog), for tools that need privileges. The app
+    /// can't kill an elevated backend, so stopping it relies on the shutdown
+    /// endpoint.
+    pub run_elevated: bool,
+    /// Backend binary to run instead of the bundled sidecar. Unofficial
+    /// backends are unsupported.
+    pub backend_binary: Option<String>,
+    /// Update channel from `channels.json` (e.g. "nightly"); when unset the
+    /// UI's stable/beta choice is used
+    pub update_channel: Option<String>,
+    /// Only ever update to exactly this version (e.g. "0.0.2"). The pin is
+    /// looked up in the manifest of the channel being checked, so pinning a
+    /// beta build only works with the beta channel selected.
+    pub pinned_version: Option<String>,
+    /// Turn the in-app updater off, for installs where IT pushes updates
+    /// (`SCOOTER_NO_UPDATE` does the same)
+    pub updates_disabled: bool,
+    /// How long a live backend may be unreachable before it's considered hung
+    pub hang_timeout_secs: u64,
+    /// Restart a hung backend automatically instead of only reporting it
+    pub restart_on_hang: bool,
+    /// Restart a crashed backend automatically; off leaves it down for debugging
+    pub restart_on_crash: bool,
+    /// Crash restarts allowed within `restart_window_secs` before giving up
+    pub max_restart_attempts: u32,
+    pub restart_window_secs: u64,
+    /// Memory the backend may use before `backend-memory-exceeded` is emitted
+    pub max_backend_memory_mb: Option<u64>,
+    /// Restart a backend that stays above `max_backend_memory_mb`
+    pub restart_on_memory_limit: bool,
+    /// Linux only: also have the kernel enforce `max_backend_memory_mb`, by
+    /// starting the backend under `systemd-run --user --scope -p MemoryMax=`
+    /// when no `sandbox_command` is set
+    pub memory_limit_cgroup: bool,
+    /// Loopback port for the agent health endpoint; off when unset
+    pub agent_health_port: Option<u16>,
+    /// Proxy for plain HTTP requests, e.g. "http://proxy.corp:3128"; when
+    /// unset `HTTP_PROXY` is used
+    pub http_proxy: Option<String>,
+    /// Proxy for HTTPS requests such as update checks; when unset `HTTPS_PROXY` is used
+    pub https_proxy: Option<String>,
+    /// Comma-separated hosts that skip the proxies; when unset `NO_PROXY` is
+    /// used. Loopback (the backend) always skips them.
+    pub no_proxy: Option<String>,
+    /// Tools whose status changes don't notify
+    pub muted_tools: Vec<String>,
+    /// Urgency of tool problem notifications; a crashed gateway is always critical
+    pub tool_notification_urgency: NotificationUrgency,
+    /// Play sounds with notifications at all
+    pub notification_sound: bool,
+    /// Backends started alongside the primary one
+    pub instances: Vec<BackendInstance>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            close_behavior: CloseBehavior::default(),
+            close_hint_shown: false,
+            tray_left_click: TrayLeftClick::default(),
+            tray_open_click: TrayOpenClick::default(),
+            always_on_top: false,
+            icon_scope: IconScope::default(),
+            tray_compact_title: false,
+            tray_tool_layout: TrayToolLayout::default(),
+            control_port: 6200,
+            mcp_port: 6277,
+            endpoints: BackendEndpoints::default(),
+            poll_interval_secs: 5,
+            tray_debounce_ms: 500,
+            data_dir: None,
+            sandbox_command: None,
+            env_inheritance: EnvInheritance::default(),
+            run_elevated: false,
+            backend_binary: None,
+            update_channel: None,
+            pinned_version: None,
+            updates_disabled: false,
+            hang_timeout_secs: 45,
+            restart_on_hang: false,
+            restart_on_crash: true,
+            max_restart_attempts: 5,
+            restart_window_secs: 60,
+            max_backend_memory_mb: None,
+            restart_on_memory_limit: false,
+            memory_limit_cgroup: false,
+            agent_health_port: None,
+            http_proxy: None,
+            https_proxy: None,
+            no_proxy: None,
+            muted_tools: Vec::new(),
+            tool_notification_urgency: NotificationUrgency::Normal,
+            notification_sound: true,
+            instances: Vec::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// What a left click on the tray icon actually does, taking
+    /// `tray_open_click` into account
+    pub fn effective_left_click(&self) -> TrayLeftClick {
+        match self.tray_open_click {
+            TrayOpenClick::Single => TrayLeftClick::Window,
+            TrayOpenClick::Double => self.tray_left_click,
+        }
+    }
+
+    /// Reject values that would leave the app unable to work
+    pub fn validate(&self) -> Result<(), String> {
+        if self.control_port == 0 || self.mcp_port == 0 {
+            return Err("Ports must be between 1 and 65535".to_string());
+        }
+        if self.control_port == self.mcp_port {
+            return Err(format!(
+                "control_port and mcp_port are both {}; the control API and the MCP gateway need different ports",
+                self.control_port
+            ));
+        }
+        for path in [&self.endpoints.status, &self.endpoints.ping, &self.endpoints.shutdown] {
+            if !path.starts_with('/') {
+                return Err(format!("Endpoint path '{}' must start with '/'", path));
+            }
+        }
+        let mut ports = vec![("control_port", self.control_port), ("mcp_port", self.mcp_port)];
+        let mut ids = vec![PRIMARY_INSTANCE];
+        for instance in &self.instances {
+            if instance.id.trim().is_empty() || ids.contains(&instance.id.as_str()) {
+                return Err(format!("Instance id '{}' is empty or already used", instance.id));
+            }
+            ids.push(instance.id.as_str());
+            if instance.data_dir.trim().is_empty() {
+                return Err(format!("Instance '{}' needs its own data_dir", instance.id));
+            }
+            for port in [instance.control_port, instance.mcp_port] {
+                if port == 0 {
+                    return Err("Ports must be between 1 and 65535".to_string());
+                }
+                if let Some((name, _)) = ports.iter().find(|(_, p)| *p == port) {
+                    return Err(format!("Instance '{}' uses port {}, which {} already uses", instance.id, port, name));
+                }
+                ports.push((instance.id.as_str(), port));
+            }
+        }
+        for proxy in [&self.http_proxy, &self.https_proxy].into_iter().flatten() {
+            if !proxy.is_empty() && !proxy.starts_with("http://") && !proxy.starts_with("https://") {
+                return Err(format!("Proxy '{}' must be an http:// or https:// URL", proxy));
+            }
+        }
+        if self.max_backend_memory_mb == Some(0) {
+            return Err("max_backend_memory_mb must be at least 1".to_string());
+        }
+        if self.poll_interval_secs == 0 {
+            return Err("poll_interval_secs must be at least 1".to_string());
+        }
+        if self.hang_timeout_secs < self.poll_interval_secs {
+            return Err("hang_timeout_secs must not be shorter than poll_interval_secs".to_string());
+        }
+        Ok(())
+    }
+
+    /// Configured ports below 1024, by setting name (e.g. "mcp_port" or
+    /// "instances[work].control_port")
+    pub fn privileged_ports(&self) -> Vec<(String, u16)> {
+        let mut ports = vec![("control_port".to_string(), self.control_port), ("mcp_port".to_string(), self.mcp_port)];
+        for instance in &self.instances {
+            ports.push((format!("instances[{}].control_port", instance.id), instance.control_port));
+            ports.push((format!("instances[{}].mcp_port", instance.id), instance.mcp_port));
+        }
+        ports.retain(|(_, port)| is_privileged_port(*port));
+        ports
+    }
+}
+
+/// Settings that only take effect after the app restarts
+pub const RESTART_REQUIRED: &[&str] = &["agent_health_port"];
+
+/// Which of `RESTART_REQUIRED` differ between two settings
+pub fn restart_required_changes(before: &Settings, after: &Settings) -> Vec<String> {
+    let (Ok(before), Ok(after)) = (serde_json::to_value(before), serde_json::to_value(after)) else {
+        return Vec::new();
+    };
+    RESTART_REQUIRED
+        .iter()
+        .filter(|key| before.get(**key) != after.get(**key))
+        .map(|key| key.to_string())
+        .collect()
+}
+
+/// Ports below 1024 need elevated privileges to bind on most Unix systems
+pub fn is_privileged_port(port: u16) -> bool {
+    port < 1024
+}
+
+/// Version 0 files predate versioning; the fields they have are unchanged
+fn migrate_v0_to_v1(_value: &mut Value) {}
+
+// Migration steps, where entry `n` upgrades version `n` to `n + 1`
+const MIGRATIONS: [fn(&mut Value); SETTINGS_VERSION as usize] = [migrate_v0_to_v1];
+
+/// Bring settings JSON written by an older build up to `SETTINGS_VERSION`
+///
+/// Files without a `version` are treated as version 0. Files from a newer
+/// build are left as they are; unknown fields are ignored when parsing.
+fn migrate(mut value: Value) -> Value {
+    let version = schema_version(&value) as usize;
+    if version >= MIGRATIONS.len() {
+        return value;
+    }
+    for (from, step) in MIGRATIONS.iter().enumerate().skip(version) {
+        println!("Migrating settings from version {} to {}", from, from + 1);
+        step(&mut value);
+    }
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert("version".to_string(), Value::from(SETTINGS_VERSION));
+    }
+    value
+}
+
+/// Schema version of settings JSON; 0 when it has none
+fn schema_version(value: &Value) -> u32 {
+    value.get("version").and_then(Value::as_u64).unwrap_or(0) as u32
+}
+
+/// Parse and migrate settings JSON, e.g. from an imported config archive
+pub fn parse_settings(data: &[u8]) -> Result<Settings, String> {
+    let value: Value = serde_json::from_slice(data).map_err(|e| e.to_string())?;
+    serde_json::from_value(migrate(value)).map_err(|e| e.to_string())
+}
+
+/// Read, migrate and parse a settings file
+///
+/// A file that needs migrating is first copied to e.g. `settings.v0.json.bak`,
+/// since the next save replaces it in the new format.
+fn read_settings(path: &Path) -> Result<Settings, String> {
+    let data = std::fs::read(path).map_err(|e| e.to_string())?;
+    let version = serde_json::from_slice::<Value>(&data).ok().map(|v| schema_version(&v));
+    if let Some(version) = version.filter(|v| *v < SETTINGS_VERSION) {
+        let backup = path.with_extension(format!("v{}.json.bak", version));
+        if let Err(e) = std::fs::copy(path, &backup) {
+            eprintln!("Warning: Failed to back up settings to {:?} before migrating: {}", backup, e);
+        }
+    }
+    parse_settings(&data)
+}
+
+/// Write a file atomically: write a sibling temp file, then rename it over
+fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, contents).map_err(|e| format!("Failed to write settings: {}", e))?;
+    std::fs::rename(&tmp, path).map_err(|e| {
+        let _ = std::fs::remove_file(&tmp);
+        format!("Failed to replace settings file: {}", e)
+    })
+}
+
+/// Managed state holding the current settings and where they're stored
+pub struct SettingsState {
+    path: Option<PathBuf>,
+    settings: Mutex<Settings>,
+}
+
+impl SettingsState {
+    /// Load the settings file, falling back to defaults if it's missing or invalid
+    pub fn load<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Self {
+        let dir = match crate::portable::dir() {
+            Some(dir) => Some(dir.to_path_buf()),
+            None => app.path().app_config_dir().ok(),
+        };
+        let path = dir.map(|d| d.join(SETTINGS_FILE));
+        let settings = match path.as_deref() {
+            Some(p) if p.exists() => read_settings(p).unwrap_or_else(|e| {
+                eprintln!("Warning: Ignoring invalid settings file {:?}: {}", p, e);
+                Settings::default()
+            }),
+            _ => Settings::default(),
+        };
+        Self {
+            path,
+            settings: Mutex::new(settings),
+        }
+    }
+
+    /// A copy of the current settings
+    pub fn get(&self) -> Settings {
+        self.settings.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    /// Apply a change and persist it, returning the new settings
+    pub fn update(&self, change: impl FnOnce(&mut Settings)) -> Result<Settings, String> {
+        let mut settings = self.settings.lock().map_err(|_| "Settings lock poisoned".to_string())?;
+        let mut updated = settings.clone();
+        change(&mut updated);
+        updated.validate()?;
+        self.save(&updated)?;
+        *settings = updated;
+        Ok(settings.clone())
+    }
+
+    /// Merge a partial JSON object into the settings and persist the result
+    ///
+    /// Only the keys present in `partial` change; unknown keys are rejected.
+    /// Changing a port to a privileged one is refused unless `allow_privileged`.
+    pub fn update_partial(&self, partial: Value, allow_privileged: bool) -> Result<Settings, String> {
+        let Value::Object(changes) = partial else {
+            return Err("Settings update must be a JSON object".to_string());
+        };
+        let mut settings = self.settings.lock().map_err(|_| "Settings lock poisoned".to_string())?;
+
+        let mut merged = serde_json::to_value(&*settings).map_err(|e| e.to_string())?;
+        let fields = merged.as_object_mut().ok_or("Settings must serialize to an object")?;
+        for (key, value) in changes {
+            if key == "version" {
+                continue;
+            }
+            if !fields.contains_key(&key) {
+                return Err(format!("Unknown setting '{}'", key));
+            }
+            fields.insert(key, value);
+        }
+
+        let updated: Settings = serde_json::from_value(merged).map_err(|e| format!("Invalid settings: {}", e))?;
+        updated.validate()?;
+        if !allow_privileged {
+            let current = settings.privileged_ports();
+            if let Some((name, port)) = updated.privileged_ports().into_iter().find(|p| !current.contains(p)) {
+                return Err(format!(
+                    "{} {} is a privileged port (below 1024); pass allow_privileged to use it anyway",
+                    name, port
+                ));
+            }
+        }
+        self.save(&updated)?;
+        *settings = updated;
+        Ok(settings.clone())
+    }
+
+    /// Schema version of the settings file, if there is a readable one
+    pub fn file_version(&self) -> Option<u32> {
+        let data = std::fs::read(self.path.as_ref()?).ok()?;
+        serde_json::from_slice::<Value>(&data).ok().map(|v| schema_version(&v))
+    }
+
+    /// Setting names present in the settings file, as opposed to defaulted
+    pub fn file_keys(&self) -> Vec<String> {
+        let Some(data) = self.path.as_ref().and_then(|p| std::fs::read(p).ok()) else {
+            return Vec::new();
+        };
+        match serde_json::from_slice::<Value>(&data) {
+            Ok(Value::Object(fields)) => fields.keys().cloned().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Write the current settings to disk again, e.g. before a restart
+    pub fn flush(&self) -> Result<(), String> {
+        let settings = self.settings.lock().map_err(|_| "Settings lock poisoned".to_string())?;
+        self.save(&settings)
+    }
+
+    fn save(&self, settings: &Settings) -> Result<(), String> {
+        let path = self.path.as_ref().ok_or("Could not resolve the app config dir")?;
+        let json = serde_json::to_vec_pretty(settings).map_err(|e| e.to_string())?;
+        write_atomic(path, &json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance(id: &str, control_port: u16, mcp_port: u16) -> BackendInstance {
+        BackendInstance {
+            id: id.to_string(),
+            name: None,
+            data_dir: format!("/tmp/{}", id),
+            control_port,
+            mcp_port,
+        }
+    }
+
+    #[test]
+    fn migrate_unversioned_file() {
+        let value = migrate(serde_json::json!({ "control_port": 7000 }));
+        assert_eq!(schema_version(&value), SETTINGS_VERSION);
+        assert_eq!(value["control_port"], 7000);
+    }
+
+    #[test]
+    fn migrate_leaves_newer_file_alone() {
+        let value = serde_json::json!({ "version": SETTINGS_VERSION + 1, "future_field": true });
+        assert_eq!(migrate(value.clone()), value);
+    }
+
+    #[test]
+    fn parse_unversioned_file() {
+        let settings = parse_settings(br#"{ "mcp_port": 7277 }"#).unwrap();
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(settings.mcp_port, 7277);
+        assert_eq!(settings.control_port, Settings::default().control_port);
+    }
+
+    #[test]
+    fn parse_newer_file() {
+        let data = format!(r#"{{ "version": {}, "mcp_port": 7277, "future_field": 1 }}"#, SETTINGS_VERSION + 1);
+        let settings = parse_settings(data.as_bytes()).unwrap();
+        assert_eq!(settings.version, SETTINGS_VERSION + 1);
+        assert_eq!(settings.mcp_port, 7277);
+    }
+
+    #[test]
+    fn parse_rejects_invalid_json() {
+        assert!(parse_settings(b"{ not json").is_err());
+    }
+
+    #[test]
+    fn validate_accepts_distinct_instances() {
+        let settings = Settings {
+            instances: vec![instance("work", 6300, 6377), instance("test", 6400, 6477)],
+            ..Settings::default()
+        };
+        assert_eq!(settings.validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_rejects_instance_id_collisions() {
+        for instances in [
+            vec![instance(PRIMARY_INSTANCE, 6300, 6377)],
+            vec![instance("work", 6300, 6377), instance("work", 6400, 6477)],
+            vec![instance(" ", 6300, 6377)],
+        ] {
+            let settings = Settings { instances, ..Settings::default() };
+            let err = settings.validate().unwrap_err();
+            assert!(err.contains("is empty or already used"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn validate_rejects_instance_port_collisions() {
+        let settings = Settings { instances: vec![instance("work", 6200, 6377)], ..Settings::default() };
+        assert_eq!(
+            settings.validate(),
+            Err("Instance 'work' uses port 6200, which control_port already uses".to_string())
+        );
+
+        let settings = Settings {
+            instances: vec![instance("work", 6300, 6377), instance("test", 6400, 6377)],
+            ..Settings::default()
+        };
+        assert_eq!(settings.validate(), Err("Instance 'test' uses port 6377, which work already uses".to_string()));
+
+        let settings = Settings { instances: vec![instance("work", 6300, 6300)], ..Settings::default() };
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn privileged_ports_include_instances() {
+        let settings = Settings {
+            mcp_port: 80,
+            instances: vec![instance("work", 443, 6377)],
+            ..Settings::default()
+        };
+        assert_eq!(
+            settings.privileged_ports(),
+            vec![("mcp_port".to_string(), 80), ("instances[work].control_port".to_string(), 443)]
+        );
+    }
+}